@@ -0,0 +1,68 @@
+use chrono::NaiveDate;
+use std::fmt;
+
+// Errors of the program, around the errors of the calculation and of the config of the library
+#[derive(Debug)]
+pub enum Error {
+    Prayer(prayer_times::Error),
+    // The notification could not be sent
+    Notification(String),
+    // The output could not be serialized
+    Serialization(String),
+    // The date is not in the expected format
+    InvalidDate { date: String, format: &'static str },
+    // The last date of a range is before its first date
+    InvalidRange { from: NaiveDate, to: NaiveDate },
+    // The duration is not made of hours and minutes, or is too long
+    InvalidDuration(String),
+    // A file could not be read or written
+    Io(String),
+    // A request to the daemon could not be understood
+    InvalidRequest(String),
+    // Snooze was asked before any notification that can be snoozed
+    NothingToSnooze,
+    // A hook command could not be started
+    Hook(String),
+    // The audio player could not be started
+    Audio(String),
+    // The daemon could not be exposed on the session bus
+    Dbus(String),
+    // Reported by the daemon in its answer
+    Daemon(String),
+}
+
+impl From<prayer_times::Error> for Error {
+    fn from(error: prayer_times::Error) -> Self {
+        Error::Prayer(error)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Prayer(error) => write!(f, "{}", error),
+            Error::Notification(reason) => write!(f, "Failed to send notification: {}", reason),
+            Error::Serialization(reason) => write!(f, "Failed to serialize output: {}", reason),
+            Error::InvalidDate { date, format } => {
+                write!(f, "Invalid date '{}'. Please use {} format.", date, format)
+            }
+            Error::InvalidRange { from, to } => {
+                write!(f, "Invalid range, {} is before {}", to, from)
+            }
+            Error::InvalidDuration(duration) => write!(
+                f,
+                "Invalid duration '{}'. Please use hours and minutes like 2h, 45m or 1h30m.",
+                duration
+            ),
+            Error::Io(reason) => write!(f, "{}", reason),
+            Error::InvalidRequest(reason) => write!(f, "Invalid request: {}", reason),
+            Error::NothingToSnooze => write!(f, "No notification to snooze"),
+            Error::Hook(reason) => write!(f, "Failed to run hook {}", reason),
+            Error::Audio(reason) => write!(f, "Failed to play audio: {}", reason),
+            Error::Dbus(reason) => write!(f, "D-Bus error: {}", reason),
+            Error::Daemon(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
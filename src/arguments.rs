@@ -2,12 +2,16 @@ pub mod generation;
 
 use std::path::PathBuf;
 
-use crate::madhab::Madhab;
-use crate::method::MethodVariant;
+use crate::app_error::Error;
+use crate::missed_policy::MissedPolicy;
 use crate::notification_urgency::NotifUrgency;
+use crate::output::OutputFormat;
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
 use clap::Args;
 use clap::Parser;
 use clap::Subcommand;
+use prayer_times::{HighLatitudeRule, HijriDate, Locale, Madhab, MethodVariant};
 
 /// Program to notify prayer times
 #[derive(Parser)]
@@ -35,6 +39,9 @@ pub struct Arguments {
     /// Madhab to use
     #[arg(short = 'M', long)]
     pub madhab: Option<Madhab>,
+    /// Rule used for Fajr and Isha in high latitude locations
+    #[arg(long)]
+    pub high_latitude: Option<HighLatitudeRule>,
     // /// Custom Fajr angle
     // #[arg(long)]
     // pub fajr_angle: Option<f64>,
@@ -56,6 +63,9 @@ pub struct Arguments {
     /// Minutes to add or remove to the Isha time
     #[arg(long, allow_hyphen_values = true)]
     pub isha_mod: Option<i8>,
+    /// Days to add or remove to the Hijri date for the local moon sighting (between -2 and 2)
+    #[arg(long, allow_hyphen_values = true)]
+    pub hijri_adjustment: Option<i8>,
 
     /// Show notification 10 minutes before prayer time [default: false]
     #[arg(long)]
@@ -63,16 +73,67 @@ pub struct Arguments {
     /// Custom icon path for notifications
     #[arg(long)]
     pub icon: Option<PathBuf>,
+    /// Show notification at the iqama of the prayers that have an iqama rule [default: false]
+    #[arg(long)]
+    pub notify_iqama: Option<bool>,
+    /// Don't play the adhan and reminder sounds of the config
+    #[arg(long)]
+    pub no_audio: bool,
 
     /// Notification urgency
     #[arg(long)]
     pub urgency: Option<NotifUrgency>,
+
+    /// What to do with a prayer missed during a suspend: Skip, Notify or NotifyWithin [default: NotifyWithin]
+    #[arg(long)]
+    pub missed: Option<MissedPolicy>,
+    /// Minutes after the prayer during which a missed prayer is still notified with NotifyWithin [default: 30]
+    #[arg(long)]
+    pub missed_within: Option<u64>,
+
+    /// Language of the prayer names and notifications: en, ar, fr, tr, ur, ms or id [default: from LANG]
+    #[arg(long, global = true)]
+    pub locale: Option<Locale>,
+
+    /// Moment to use instead of the current time, in YYYY-MM-DDTHH:MM format in the timezone or in RFC 3339 format
+    #[arg(long, global = true)]
+    pub now: Option<String>,
+
+    /// Output format: plain, json, csv or tsv
+    #[arg(long, global = true, default_value_t)]
+    pub format: OutputFormat,
+}
+
+impl Arguments {
+    // Whether the prayers differ from the ones of the config, the daemon cannot answer then
+    pub fn changes_calculation(&self) -> bool {
+        self.latitude.is_some()
+            || self.longitude.is_some()
+            || self.timezone.is_some()
+            || self.method.is_some()
+            || self.madhab.is_some()
+            || self.high_latitude.is_some()
+            || [
+                self.fajr_mod,
+                self.dhuhr_mod,
+                self.asr_mod,
+                self.maghrib_mod,
+                self.isha_mod,
+                self.hijri_adjustment,
+            ]
+            .iter()
+            .any(Option::is_some)
+            || self.locale.is_some()
+            || self.now.is_some()
+    }
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
     /// Start the process that will send notifications on prayers time [default]
     Daemon(DaemonArgs),
+    /// Show the next prayer and notification of the running daemon
+    Status,
     /// Get the previous prayer
     Previous,
     /// Get the current prayer
@@ -85,10 +146,25 @@ pub enum Commands {
     Methods,
     /// List all madhab available for the calculation of the prayer times
     Madhab,
+    /// List all rules available for Fajr and Isha in high latitude locations
+    HighLatitude,
     /// Show the next prayer in a notification to test if everything works
     // DryRun,
     /// Get the path of the toml config file
     Config,
+    /// Export the prayers of a range of dates to another format
+    #[command(subcommand)]
+    Export(ExportCommands),
+    /// Show a table of the prayers of a range of dates (defaults to current month)
+    Timetable(DateRangeArgs),
+    /// Convert a date to the Hijri calendar (defaults to current Hijri date)
+    Hijri(HijriArgs),
+    /// Show the direction and the distance of the Kaaba, and the Qibla days of the year
+    Qibla(QiblaArgs),
+    /// Pause the notifications of the daemon for a while
+    Pause(PauseArgs),
+    /// Resume the notifications paused with `pause`
+    Resume,
     /// Generate shell completions and man pages
     GenerateShell,
 }
@@ -101,7 +177,7 @@ impl Default for Commands {
 
 #[derive(Args)]
 pub struct DaemonArgs {
-    /// Interval in seconds for checking new prayers
+    /// Longest time in seconds between two checks of the time [default: until the next notification]
     #[arg(short, long)]
     pub interval: Option<u64>,
 }
@@ -112,3 +188,245 @@ pub struct ListPrayersArgs {
     #[arg(short, long)]
     pub date: Option<String>,
 }
+
+#[derive(Args)]
+pub struct HijriArgs {
+    /// Gregorian date to convert in YYYY-MM-DD format
+    #[arg(short, long, conflicts_with = "from_hijri")]
+    pub date: Option<String>,
+    /// Hijri date to convert to the gregorian calendar in YYYY-MM-DD format
+    #[arg(long)]
+    pub from_hijri: Option<String>,
+}
+
+#[derive(Args)]
+pub struct QiblaArgs {
+    /// Magnetic declination in degrees, positive east, to also show the magnetic bearing
+    #[arg(long, allow_hyphen_values = true)]
+    pub declination: Option<f64>,
+    /// Year of the Qibla days (defaults to current year)
+    #[arg(long)]
+    pub year: Option<i32>,
+}
+
+#[derive(Args)]
+pub struct PauseArgs {
+    /// Length of the pause, like 2h, 45m or 1h30m
+    #[arg(long = "for")]
+    pub duration: String,
+}
+
+#[derive(Subcommand)]
+pub enum ExportCommands {
+    /// Export the prayers as an iCalendar (.ics) file
+    Ics(IcsArgs),
+}
+
+#[derive(Args)]
+pub struct IcsArgs {
+    #[command(flatten)]
+    pub range: DateRangeArgs,
+    /// File to write the calendar to (defaults to the standard output)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct DateRangeArgs {
+    /// First date in YYYY-MM-DD format
+    #[arg(long, conflicts_with_all = ["month", "year"])]
+    pub from: Option<String>,
+    /// Last date in YYYY-MM-DD format (defaults to the first date)
+    #[arg(long, requires = "from", conflicts_with_all = ["month", "year"])]
+    pub to: Option<String>,
+    /// Month in YYYY-MM format
+    #[arg(long, conflicts_with = "year")]
+    pub month: Option<String>,
+    /// Year in YYYY format
+    #[arg(long)]
+    pub year: Option<i32>,
+}
+
+impl DateRangeArgs {
+    // First and last dates of the range, `default` is used when no date is given
+    pub fn dates(&self, default: (NaiveDate, NaiveDate)) -> Result<(NaiveDate, NaiveDate), Error> {
+        if let Some(month_str) = &self.month {
+            let first_day = NaiveDate::parse_from_str(&format!("{}-01", month_str), "%Y-%m-%d")
+                .map_err(|_| Error::InvalidDate {
+                    date: month_str.clone(),
+                    format: "YYYY-MM",
+                })?;
+            return Ok(month_range(first_day));
+        }
+        if let Some(year) = self.year {
+            let invalid_year = || Error::InvalidDate {
+                date: year.to_string(),
+                format: "YYYY",
+            };
+            let first_day = NaiveDate::from_ymd_opt(year, 1, 1).ok_or_else(invalid_year)?;
+            let last_day = NaiveDate::from_ymd_opt(year, 12, 31).ok_or_else(invalid_year)?;
+            return Ok((first_day, last_day));
+        }
+
+        let Some(from_str) = &self.from else {
+            return Ok(default);
+        };
+        let from = parse_date(from_str)?;
+        let to = match &self.to {
+            Some(date_str) => parse_date(date_str)?,
+            None => from,
+        };
+        Ok((from, to))
+    }
+}
+
+// First and last days of the month of the date
+pub fn month_range(date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let first_day = date.with_day(1).unwrap_or(date);
+    let last_day = first_day
+        .checked_add_months(Months::new(1))
+        .and_then(|next_month| next_month.pred_opt())
+        .unwrap_or(date);
+    (first_day, last_day)
+}
+
+pub fn parse_date(date_str: &str) -> Result<NaiveDate, Error> {
+    NaiveDate::parse_from_str(date_str, "%Y-%m-%d").map_err(|_| Error::InvalidDate {
+        date: date_str.to_string(),
+        format: "YYYY-MM-DD",
+    })
+}
+
+// Moment in the timezone, or with its own offset
+pub fn parse_now(now_str: &str, timezone: Tz) -> Result<DateTime<Utc>, Error> {
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(now_str) {
+        return Ok(date_time.to_utc());
+    }
+    ["%Y-%m-%dT%H:%M", "%Y-%m-%dT%H:%M:%S"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(now_str, format).ok())
+        // The earliest moment when the local time happens twice at the end of the DST
+        .and_then(|naive| timezone.from_local_datetime(&naive).earliest())
+        .map(|date_time| date_time.to_utc())
+        .ok_or_else(|| Error::InvalidDate {
+            date: now_str.to_string(),
+            format: "YYYY-MM-DDTHH:MM",
+        })
+}
+
+// Duration made of hours and minutes, like 2h, 45m or 1h30m
+pub fn parse_duration(duration_str: &str) -> Result<Duration, Error> {
+    let invalid_duration = || Error::InvalidDuration(duration_str.to_string());
+    let mut duration = Duration::zero();
+    let mut number = String::new();
+    for character in duration_str.trim().chars() {
+        match character {
+            '0'..='9' => number.push(character),
+            'h' | 'm' => {
+                let value: i64 = number.parse().map_err(|_| invalid_duration())?;
+                let part = match character {
+                    'h' => Duration::try_hours(value),
+                    _ => Duration::try_minutes(value),
+                };
+                duration = part
+                    .and_then(|part| duration.checked_add(&part))
+                    .ok_or_else(invalid_duration)?;
+                number.clear();
+            }
+            _ => return Err(invalid_duration()),
+        }
+    }
+    if !number.is_empty() || duration <= Duration::zero() {
+        return Err(invalid_duration());
+    }
+    Ok(duration)
+}
+
+// End of a pause of the duration from now
+pub fn parse_pause_end(now: DateTime<Utc>, duration_str: &str) -> Result<DateTime<Utc>, Error> {
+    now.checked_add_signed(parse_duration(duration_str)?)
+        .ok_or_else(|| Error::InvalidDuration(duration_str.to_string()))
+}
+
+pub fn parse_hijri_date(date_str: &str) -> Result<HijriDate, Error> {
+    let invalid_date = || Error::InvalidDate {
+        date: date_str.to_string(),
+        format: "YYYY-MM-DD",
+    };
+    let mut parts = date_str.splitn(3, '-');
+    let mut next_part = || parts.next().ok_or_else(invalid_date);
+    let year = next_part()?.parse().map_err(|_| invalid_date())?;
+    let month = next_part()?.parse().map_err(|_| invalid_date())?;
+    let day = next_part()?.parse().map_err(|_| invalid_date())?;
+    Ok(HijriDate { year, month, day })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("2h").unwrap(), Duration::hours(2));
+        assert_eq!(parse_duration("45m").unwrap(), Duration::minutes(45));
+        assert_eq!(parse_duration(" 1h30m ").unwrap(), Duration::minutes(90));
+        for invalid in ["", "0m", "90", "1d", "h", "1h30"] {
+            assert!(
+                matches!(parse_duration(invalid), Err(Error::InvalidDuration(_))),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_oversized_duration() {
+        // Too many hours for a duration, or too many for a date
+        for oversized in [
+            "99999999999999999h",
+            "2562047788016h",
+            "153722867280912m1m",
+            "99999999999999999999m",
+        ] {
+            assert!(
+                matches!(parse_duration(oversized), Err(Error::InvalidDuration(_))),
+                "{}",
+                oversized
+            );
+        }
+        let now = Utc.with_ymd_and_hms(2026, 3, 19, 10, 0, 0).unwrap();
+        assert!(matches!(
+            parse_pause_end(now, "99999999999h"),
+            Err(Error::InvalidDuration(_))
+        ));
+        assert_eq!(
+            parse_pause_end(now, "1h30m").unwrap(),
+            Utc.with_ymd_and_hms(2026, 3, 19, 11, 30, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_to_requires_from() {
+        let result = Arguments::try_parse_from(["prayer-times", "timetable", "--to", "2026-03-31"]);
+        assert!(result.is_err());
+
+        let arguments = Arguments::try_parse_from([
+            "prayer-times",
+            "timetable",
+            "--from",
+            "2026-03-01",
+            "--to",
+            "2026-03-31",
+        ])
+        .unwrap();
+        let Some(Commands::Timetable(range)) = arguments.command else {
+            panic!("Expected the timetable command");
+        };
+        let first_day = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let last_day = NaiveDate::from_ymd_opt(2026, 3, 31).unwrap();
+        assert_eq!(
+            range.dates((last_day, last_day)).unwrap(),
+            (first_day, last_day)
+        );
+    }
+}
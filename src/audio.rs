@@ -0,0 +1,170 @@
+use crate::app_error::Error;
+use crate::config::Config;
+use crate::notification::Notifier;
+use chrono::Duration;
+use prayer_times::{Event, Prayer};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+// Time between two checks of a running player
+const POLL_MILLISECONDS: u64 = 200;
+
+// A sound being played by the external player, it can be stopped from another thread
+#[derive(Clone)]
+pub struct Playback {
+    child: Arc<Mutex<Child>>,
+}
+
+impl Playback {
+    pub fn stop(&self) {
+        if let Ok(mut child) = self.child.lock() {
+            // The player may already have exited
+            if let Ok(None) = child.try_wait() {
+                println!("Stopping the audio");
+                if let Err(error) = child.kill() {
+                    eprintln!("Failed to stop the audio player: {}", error);
+                }
+            }
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.child
+            .lock()
+            .is_ok_and(|mut child| matches!(child.try_wait(), Ok(None)))
+    }
+}
+
+// The sound currently played, shared with the desktop notifications to stop it on dismiss
+pub type CurrentPlayback = Arc<Mutex<Option<Playback>>>;
+
+// Play a file with the player command, `{file}` and `{volume}` (in percent) are replaced in its
+// arguments, the file is added at the end without `{file}`
+pub fn play(player: &str, file: &Path, volume: u8) -> Result<Playback, Error> {
+    if player.trim().is_empty() {
+        return Err(Error::Audio("no player command".to_string()));
+    }
+    let file = file.to_string_lossy();
+    let mut args: Vec<String> = player
+        .split_whitespace()
+        .map(|arg| {
+            arg.replace("{file}", &file)
+                .replace("{volume}", &volume.to_string())
+        })
+        .collect();
+    if !player.contains("{file}") {
+        args.push(file.to_string());
+    }
+
+    let child = Command::new(&args[0])
+        .args(&args[1..])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|error| Error::Audio(format!("{}: {}", args[0], error)))?;
+    println!("Playing {}", file);
+
+    let playback = Playback {
+        child: Arc::new(Mutex::new(child)),
+    };
+    // Reap the player when it exits so it doesn't stay a zombie
+    let reaped = playback.clone();
+    thread::spawn(move || {
+        while reaped.is_playing() {
+            thread::sleep(std::time::Duration::from_millis(POLL_MILLISECONDS));
+        }
+    });
+    Ok(playback)
+}
+
+// Plays the adhan at the prayers and a chime for the reminders
+pub struct AudioNotifier {
+    pub current: CurrentPlayback,
+}
+
+impl AudioNotifier {
+    fn play(&self, file: Option<&Path>, config: &Config) -> Result<(), Error> {
+        let Some(file) = file else {
+            return Ok(());
+        };
+        let Ok(mut current) = self.current.lock() else {
+            return Ok(());
+        };
+        // A new sound replaces the previous one
+        if let Some(previous) = current.take() {
+            previous.stop();
+        }
+        *current = Some(play(config.audio_player(), file, config.audio_volume())?);
+        Ok(())
+    }
+}
+
+impl Notifier for AudioNotifier {
+    fn prayer(&self, prayer: &Prayer, config: &Config) -> Result<(), Error> {
+        self.play(config.adhan_sound(prayer.event()), config)
+    }
+    fn reminder(&self, _prayer: &Prayer, _offset: Duration, config: &Config) -> Result<(), Error> {
+        self.play(config.chime_sound(), config)
+    }
+    fn missed_prayer(&self, _prayer: &Prayer, _config: &Config) -> Result<(), Error> {
+        Ok(())
+    }
+    fn iqama(&self, _prayer: &Prayer, config: &Config) -> Result<(), Error> {
+        self.play(config.chime_sound(), config)
+    }
+    fn departure(&self, _prayer: &Prayer, config: &Config) -> Result<(), Error> {
+        self.play(config.chime_sound(), config)
+    }
+    fn next_prayer(&self, _next: &Prayer, _reached: Option<&Prayer>) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+// Sunrise, Sunset and Midnight are not prayers, they get the chime instead of the adhan
+pub fn has_adhan(event: Event) -> bool {
+    matches!(
+        event,
+        Event::Fajr | Event::Dhuhr | Event::Asr | Event::Maghrib | Event::Isha
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Waits for the player so its output can be read
+    fn wait(playback: &Playback) {
+        while playback.is_playing() {
+            thread::sleep(std::time::Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_player_arguments() {
+        // Dummy player writing its arguments next to it
+        let directory = std::env::temp_dir().join(format!("prayer-times-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let script = directory.join("player.sh");
+        std::fs::write(&script, "echo \"$@\" > \"$(dirname \"$0\")/arguments\"").unwrap();
+
+        let player = format!("sh {} --volume={{volume}}", script.display());
+        let playback = play(&player, Path::new("/tmp/adhan.mp3"), 80).unwrap();
+        wait(&playback);
+        let arguments = std::fs::read_to_string(directory.join("arguments")).unwrap();
+        std::fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(arguments.trim(), "--volume=80 /tmp/adhan.mp3");
+
+        assert!(play(" ", Path::new("/tmp/adhan.mp3"), 100).is_err());
+    }
+
+    #[test]
+    fn test_stop_playback() {
+        let playback = play("sleep {file}", Path::new("10"), 100).unwrap();
+        assert!(playback.is_playing());
+        playback.stop();
+        wait(&playback);
+        assert!(!playback.is_playing());
+    }
+}
@@ -1,7 +1,14 @@
-use crate::{config::Config, event::Event, method::ParamValue};
-use chrono::{Datelike, Days, NaiveDate, NaiveDateTime, NaiveTime};
-
-mod math;
+use crate::{
+    error::{Error, Result},
+    event::Event,
+    high_latitude::{HighLatitudeRule, NEAREST_LATITUDE},
+    method::ParamValue,
+    parameters::Parameters,
+};
+use chrono::{DateTime, Datelike, Days, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use chrono_tz::Tz;
+
+pub(crate) mod math;
 
 fn positive_mod(value: f64, modulus: f64) -> f64 {
     let result = value - modulus * (value / modulus).floor();
@@ -22,10 +29,10 @@ fn normalize_hours(hour: f64) -> f64 {
 }
 
 // https://orbital-mechanics.space/reference/julian-date.html
-fn julian_day(date: NaiveDate) -> f64 {
+pub(crate) fn julian_day(date: NaiveDate) -> f64 {
     let day = date.day() as i32;
     let month = date.month() as i32;
-    let year = date.year() as i32;
+    let year = date.year();
 
     let a = (month - 14) / 12;
     let b = 1461 * (year + 4800 + a);
@@ -35,9 +42,31 @@ fn julian_day(date: NaiveDate) -> f64 {
     (b / 4 + c / 12 - 3 * e / 4 + day - 32075) as f64
 }
 
+// Declination of the sun in degrees and equation of time in hours at a julian date
+// https://praytimes.org/calculation#astronomical_measures
+pub(crate) fn sun_position(julian_date: f64) -> (f64, f64) {
+    let d = julian_date - 2451545.0;
+
+    let g = normalize_degrees(357.529 + 0.98560028 * d);
+    let q = normalize_degrees(280.459 + 0.98564736 * d);
+    let l = normalize_degrees(q + 1.915 * math::dsin(g) + 0.020 * math::dsin(2. * g));
+    let e = 23.439 - 0.00000036 * d;
+    let ra = math::darctan2(math::dcos(e) * math::dsin(l), math::dcos(l)) / 15.;
+
+    let declination_of_sun = math::darcsin(math::dsin(e) * math::dsin(l));
+    // Around the March equinox `q` and `ra` wrap at different moments, which would give a
+    // difference of about 24 hours
+    let equation_of_time = normalize_hours(q / 15. - ra + 12.) - 12.;
+
+    (declination_of_sun, equation_of_time)
+}
+
 #[derive(Clone)]
 pub struct AstronomicalMeasures {
     date: NaiveDate,
+    timezone: Tz,
+    // UTC offset in minutes used for the times of the day
+    utc_offset: i64,
     fajr: f64,
     sunrise: f64,
     dhuhr: f64,
@@ -49,47 +78,32 @@ pub struct AstronomicalMeasures {
     // third_of_night: f64,
 }
 impl AstronomicalMeasures {
-    pub fn new(date: NaiveDate, config: &Config) -> Self {
-        // https://praytimes.org/calculation#astronomical_measures
-        let (declination_of_sun, equation_of_time) = {
-            let julian_day = julian_day(date);
-
-            let d = julian_day - 2451545.0;
-
-            let g = normalize_degrees(357.529 + 0.98560028 * d);
-            let q = normalize_degrees(280.459 + 0.98564736 * d);
-            let l = normalize_degrees(q + 1.915 * math::dsin(g) + 0.020 * math::dsin(2. * g));
-            let e = 23.439 - 0.00000036 * d;
-            let ra = math::darctan2(math::dcos(e) * math::dsin(l), math::dcos(l)) / 15.;
-
-            let declination_of_sun = math::darcsin(math::dsin(e) * math::dsin(l));
-            let equation_of_time = q / 15. - normalize_hours(ra);
-            // println!("dec: {}, eq: {}", declination_of_sun, equation_of_time);
-
-            (declination_of_sun, equation_of_time)
-        };
+    pub fn new(date: NaiveDate, parameters: &Parameters) -> Self {
+        let utc_offset = parameters.timezone_offset(date);
+        let (declination_of_sun, equation_of_time) = sun_position(julian_day(date));
 
-        let solar_hour_angle = |angle: f64| -> f64 {
+        let hour_angle_at = |latitude: f64, angle: f64| -> f64 {
             let numerator =
-                -math::dsin(angle) - math::dsin(config.lat()) * math::dsin(declination_of_sun);
-            let denominator = math::dcos(config.lat()) * math::dcos(declination_of_sun);
+                -math::dsin(angle) - math::dsin(latitude) * math::dsin(declination_of_sun);
+            let denominator = math::dcos(latitude) * math::dcos(declination_of_sun);
             1. / 15. * math::darccos(numerator / denominator)
         };
+        let solar_hour_angle = |angle: f64| -> f64 { hour_angle_at(parameters.lat(), angle) };
 
         // https://praytimes.org/calculation#dhuhr
         let dhuhr = {
-            let a = 12. + config.timezone_offset() as f64;
-            let b = config.lon() / 15.;
+            let a = 12. + utc_offset as f64 / 60.;
+            let b = parameters.lon() / 15.;
             let c = equation_of_time;
             a - b - c
         };
         // https://praytimes.org/calculation#asr
         let asr = {
-            let t = config.shadow_multiplier() as f64;
-            let i = math::darccot(t + math::dtan((config.lat() - declination_of_sun).abs()));
-            // let i = math::darccot(t + math::dtan(config.lat() - declination_of_sun));
-            let a = math::dsin(i) - math::dsin(config.lat()) * math::dsin(declination_of_sun);
-            let b = math::dcos(config.lat()) * math::dcos(declination_of_sun);
+            let t = parameters.shadow_multiplier() as f64;
+            let i = math::darccot(t + math::dtan((parameters.lat() - declination_of_sun).abs()));
+            // let i = math::darccot(t + math::dtan(parameters.lat() - declination_of_sun));
+            let a = math::dsin(i) - math::dsin(parameters.lat()) * math::dsin(declination_of_sun);
+            let b = math::dcos(parameters.lat()) * math::dcos(declination_of_sun);
             1. / 15. * math::darccos(a / b)
         };
 
@@ -103,30 +117,66 @@ impl AstronomicalMeasures {
         };
         let diff_night = full_sunrise - sunset;
 
+        // https://praytimes.org/calculation#higher_latitudes
+        let rule = parameters.high_latitude;
+        let twilight_hour_angle = |angle: f64| -> f64 {
+            if rule == HighLatitudeRule::NearestLatitude
+                && parameters.lat().abs() > NEAREST_LATITUDE
+            {
+                hour_angle_at(NEAREST_LATITUDE.copysign(parameters.lat()), angle)
+            } else {
+                solar_hour_angle(angle)
+            }
+        };
+        // Limit the time between the twilight and the sunrise/sunset to a portion of the night
+        let limit_twilight = |time: f64, base: f64, angle: f64, before_base: bool| -> f64 {
+            let max_duration = rule.night_portion(angle) * diff_night;
+            let duration = if before_base {
+                base - time
+            } else {
+                time - base
+            };
+            let exceeds_portion =
+                rule != HighLatitudeRule::NearestLatitude && duration > max_duration;
+            if !time.is_nan() && !exceeds_portion {
+                time
+            } else if before_base {
+                base - max_duration
+            } else {
+                base + max_duration
+            }
+        };
+
         let fajr = {
-            let fajr_param = config.fajr_param();
+            let fajr_param = parameters.fajr_param();
             match fajr_param {
-                ParamValue::Angle(angle) => dhuhr - solar_hour_angle(angle),
+                ParamValue::Angle(angle) => {
+                    limit_twilight(dhuhr - twilight_hour_angle(angle), sunrise, angle, true)
+                }
                 ParamValue::Minutes(minutes) => dhuhr - minutes as f64 / 60.,
             }
         };
         let isha = {
-            let isha_param = config.isha_param();
+            let isha_param = parameters.isha_param();
             match isha_param {
-                ParamValue::Angle(angle) => dhuhr + solar_hour_angle(angle),
+                ParamValue::Angle(angle) => {
+                    limit_twilight(dhuhr + twilight_hour_angle(angle), sunset, angle, false)
+                }
                 ParamValue::Minutes(minutes) => sunset + minutes as f64 / 60.,
             }
         };
 
         Self {
             date,
+            timezone: parameters.timezone,
+            utc_offset,
             sunrise,
-            fajr: fajr + config.offset(Event::Fajr),
-            dhuhr: dhuhr + config.offset(Event::Dhuhr),
-            asr: dhuhr + asr + config.offset(Event::Asr),
+            fajr: fajr + parameters.offset(Event::Fajr),
+            dhuhr: dhuhr + parameters.offset(Event::Dhuhr),
+            asr: dhuhr + asr + parameters.offset(Event::Asr),
             sunset,
-            maghrib: sunset + config.offset(Event::Maghrib),
-            isha: isha + config.offset(Event::Isha),
+            maghrib: sunset + parameters.offset(Event::Maghrib),
+            isha: isha + parameters.offset(Event::Isha),
             midnight: sunset + 0.5 * diff_night,
             // third_of_night: sunset + 0.75 * diff_night,
         }
@@ -149,25 +199,38 @@ impl AstronomicalMeasures {
         }
     }
 
-    pub fn date_time(&self, event: Event) -> NaiveDateTime {
+    pub fn date_time(&self, event: Event) -> Result<DateTime<Tz>> {
         let time = self.raw_time(event);
+        let undefined = Error::UndefinedCalculation {
+            event,
+            date: self.date(),
+        };
+        // The sun doesn't reach the angle of the event on that day (polar day or night)
+        if !time.is_finite() {
+            return Err(undefined);
+        }
 
-        let naive_time =
-            NaiveTime::from_num_seconds_from_midnight_opt((time.rem_euclid(24.) * 3600.) as u32, 0);
+        let Some(naive_time) =
+            NaiveTime::from_num_seconds_from_midnight_opt((time.rem_euclid(24.) * 3600.) as u32, 0)
+        else {
+            return Err(undefined);
+        };
 
         let time_shift = (time / 24.).floor();
         let date = if time_shift >= 1. {
-            self.date()
-                .checked_add_days(Days::new(time_shift as u64))
-                .unwrap()
+            self.date().checked_add_days(Days::new(time_shift as u64))
         } else if time_shift < 0. {
-            self.date()
-                .checked_sub_days(Days::new(-time_shift as u64))
-                .unwrap()
+            self.date().checked_sub_days(Days::new(-time_shift as u64))
         } else {
-            self.date()
+            Some(self.date())
         };
-        // TODO: do not have an expect here
-        NaiveDateTime::new(date, naive_time.expect("Error in prayer calculation"))
+        let Some(date) = date else {
+            return Err(undefined);
+        };
+
+        let local = NaiveDateTime::new(date, naive_time);
+        // Going through UTC keeps the times right on days with a DST transition
+        let utc = local - Duration::minutes(self.utc_offset);
+        Ok(self.timezone.from_utc_datetime(&utc))
     }
 }
@@ -0,0 +1,118 @@
+use crate::app_error::Error;
+use crate::config::system_timezone;
+use crate::daemon::Message;
+use chrono_tz::Tz;
+use nix::errno::Errno;
+use nix::sys::time::TimeSpec;
+use nix::sys::timerfd::{ClockId, Expiration, TimerFd, TimerFlags, TimerSetTimeFlags};
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::Duration;
+
+// Time between two checks of the system timezone
+const TIMEZONE_POLL_SECONDS: u64 = 10;
+
+// Wake the daemon up when the system time is changed or the system resumes from a suspend, so
+// that it can sleep until its next notification. The kernel cancels a timer on the wall clock
+// armed with TFD_TIMER_CANCEL_ON_SET in both cases
+pub fn watch_clock(messages: Sender<Message>) -> Result<(), Error> {
+    let timer_error =
+        |error: Errno| Error::Io(format!("Failed to watch the system time: {}", error));
+    let timer =
+        TimerFd::new(ClockId::CLOCK_REALTIME, TimerFlags::TFD_CLOEXEC).map_err(timer_error)?;
+    let arm = move |timer: &TimerFd| {
+        // Far enough to never expire, only the cancellation matters
+        timer.set(
+            Expiration::OneShot(TimeSpec::new(nix::libc::time_t::MAX, 0)),
+            TimerSetTimeFlags::TFD_TIMER_ABSTIME | TimerSetTimeFlags::TFD_TIMER_CANCEL_ON_SET,
+        )
+    };
+    arm(&timer).map_err(timer_error)?;
+    thread::spawn(move || loop {
+        match nix::unistd::read(&timer, &mut [0u8; 8]) {
+            Err(Errno::ECANCELED) => {
+                // The daemon is gone when the channel is closed
+                if messages.send(Message::Wake("system time changed")).is_err() {
+                    return;
+                }
+                // The timer stays cancelled until it is armed again
+                if let Err(error) = arm(&timer) {
+                    eprintln!("Failed to watch the system time: {}", error);
+                    return;
+                }
+            }
+            Ok(_) | Err(Errno::EINTR) => {}
+            Err(error) => {
+                eprintln!("Failed to watch the system time: {}", error);
+                return;
+            }
+        }
+    });
+    Ok(())
+}
+
+// Wake the daemon up when the system timezone changes, nothing notifies it
+pub fn watch_timezone(messages: Sender<Message>) {
+    poll_timezone(
+        system_timezone,
+        Duration::from_secs(TIMEZONE_POLL_SECONDS),
+        messages,
+    );
+}
+
+fn poll_timezone(
+    timezone: impl Fn() -> Tz + Send + 'static,
+    interval: Duration,
+    messages: Sender<Message>,
+) {
+    thread::spawn(move || {
+        let mut last = timezone();
+        loop {
+            thread::sleep(interval);
+            let current = timezone();
+            if current != last {
+                if messages.send(Message::Wake("timezone changed")).is_err() {
+                    return;
+                }
+                last = current;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_poll_timezone() {
+        let timezone = Arc::new(Mutex::new(Tz::Europe__Paris));
+        let current = Arc::clone(&timezone);
+        let (sender, messages) = mpsc::channel();
+        poll_timezone(
+            move || *current.lock().unwrap(),
+            Duration::from_millis(10),
+            sender,
+        );
+
+        // Nothing until the timezone changes
+        assert!(messages.recv_timeout(Duration::from_millis(100)).is_err());
+        *timezone.lock().unwrap() = Tz::Asia__Tokyo;
+        let reason = match messages.recv_timeout(Duration::from_secs(2)) {
+            Ok(Message::Wake(reason)) => Some(reason),
+            _ => None,
+        };
+        assert_eq!(reason, Some("timezone changed"));
+        assert!(messages.recv_timeout(Duration::from_millis(100)).is_err());
+    }
+
+    #[test]
+    fn test_watch_clock() {
+        // Nothing happens while the system time runs normally
+        let (sender, messages) = mpsc::channel();
+        watch_clock(sender).unwrap();
+        assert!(messages.recv_timeout(Duration::from_millis(100)).is_err());
+    }
+}
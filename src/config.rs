@@ -1,20 +1,34 @@
 use std::path;
 
+use crate::app_error::Error;
 use crate::arguments::Commands;
-use crate::event::Event;
-use crate::location::current_location;
-use crate::location::Location;
-use crate::madhab::Madhab;
-use crate::method::{MethodVariant, ParamValue};
+use crate::audio::has_adhan;
+use crate::auto_location::current_location;
+use crate::missed_policy::MissedPolicy;
+use crate::notification::NotificationKind;
 use crate::notification_urgency::NotifUrgency;
+use crate::quiet::QuietConfig;
 use crate::Arguments;
-use chrono::Local;
-use chrono::{TimeZone, Utc};
-use chrono_tz::OffsetComponents;
-use notify_rust::Urgency;
+use chrono_tz::Tz;
+use prayer_times::hijri::MAX_ADJUSTMENT;
+use prayer_times::Error as PrayerError;
+use prayer_times::{
+    Event, HighLatitudeRule, IqamaRule, IqamaSchedule, IqamaSeason, Jumuah, Locale, Location,
+    Madhab, MethodVariant, Parameters,
+};
 use serde::Deserialize;
 use serde::Serialize;
-use std::error::Error;
+use std::collections::BTreeMap;
+use std::error::Error as _;
+
+// Minutes before the prayer for the notification, when enabled
+pub const NOTIFY_BEFORE_MINUTES: i64 = 10;
+// Player used for the adhan, `{volume}` is replaced by the volume in percent
+const AUDIO_PLAYER: &str = "mpv --no-video --really-quiet --volume={volume}";
+// Seconds before a hook command is killed, by default
+const HOOK_TIMEOUT_SECONDS: u64 = 30;
+// Minutes after the prayer during which a missed prayer is notified, by default
+const MISSED_WITHIN_MINUTES: u64 = 30;
 
 #[derive(Serialize, Deserialize, Clone)]
 struct PrayerConfig {
@@ -25,27 +39,224 @@ struct PrayerConfig {
     asr_mod: i8,
     maghrib_mod: i8,
     isha_mod: i8,
+    #[serde(default)]
+    high_latitude: HighLatitudeRule,
+    #[serde(default)]
+    iqama: BTreeMap<Event, IqamaRule>,
+    // Iqama rules replacing the usual ones between two days of the year
+    #[serde(default)]
+    iqama_seasons: Vec<IqamaSeason>,
 }
 #[derive(Serialize, Deserialize, Clone)]
 struct NotificationConfig {
     notify_before: bool,
     urgency: NotifUrgency,
     icon: path::PathBuf,
-    interval: u64,
+    // Longest time in seconds the daemon sleeps before checking the time again
+    #[serde(default)]
+    interval: Option<u64>,
+    #[serde(default)]
+    missed: MissedPolicy,
+    #[serde(default = "default_missed_within")]
+    missed_within: u64,
+    // Minutes from each prayer to its reminders, negative before the prayer
+    #[serde(default)]
+    reminders: BTreeMap<Event, Vec<i64>>,
+    #[serde(default)]
+    notify_iqama: bool,
+    #[serde(default)]
+    templates: TemplatesConfig,
+    // Styles of each kind of notification, then of the adhan and missed notifications of each event
+    #[serde(default)]
+    styles: StylesConfig,
+    #[serde(default)]
+    events: BTreeMap<Event, NotificationStyle>,
+}
+// How a notification is shown, the unset fields come from the more general style
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct NotificationStyle {
+    pub urgency: Option<NotifUrgency>,
+    pub icon: Option<path::PathBuf>,
+    // Seconds before the notification expires, 0 for never
+    pub timeout: Option<u32>,
+    // Category hint of the notification specification, like "x-prayer.adhan"
+    pub category: Option<String>,
+    // Kept after an action is picked
+    pub resident: Option<bool>,
+    // Not kept in the history of the notifications
+    pub transient: Option<bool>,
+}
+impl NotificationStyle {
+    // The fields set in `other` replace the ones of this style
+    fn with(self, other: &NotificationStyle) -> NotificationStyle {
+        NotificationStyle {
+            urgency: other.urgency.clone().or(self.urgency),
+            icon: other.icon.clone().or(self.icon),
+            timeout: other.timeout.or(self.timeout),
+            category: other.category.clone().or(self.category),
+            resident: other.resident.or(self.resident),
+            transient: other.transient.or(self.transient),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(icon) = &self.icon {
+            if !icon.exists() {
+                return Err(format!("icon {} does not exist", icon.display()));
+            }
+        }
+        if self
+            .timeout
+            .is_some_and(|timeout| timeout > u32::MAX / 1000)
+        {
+            return Err(format!("timeout cannot exceed {} seconds", u32::MAX / 1000));
+        }
+        if let Some(category) = &self.category {
+            if category.is_empty() || category.contains(char::is_whitespace) {
+                return Err(format!("invalid category '{}'", category));
+            }
+        }
+        if self.resident == Some(true) && self.transient == Some(true) {
+            return Err("a notification cannot be both resident and transient".to_string());
+        }
+        Ok(())
+    }
+}
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+struct StylesConfig {
+    adhan: NotificationStyle,
+    reminder: NotificationStyle,
+    iqama: NotificationStyle,
+    missed: NotificationStyle,
+    departure: NotificationStyle,
+}
+// Texts of a notification with placeholders, the default texts of the locale are used without them
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Template {
+    pub summary: Option<String>,
+    pub body: Option<String>,
+}
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+struct TemplatesConfig {
+    adhan: Template,
+    reminder: Template,
+    iqama: Template,
+    missed: Template,
+    departure: Template,
+}
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct HijriConfig {
+    // Days to add or remove to the Hijri date for the local moon sighting
+    adjustment: i8,
+}
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+struct JumuahConfig {
+    // Report Dhuhr as Jumu'ah on Fridays
+    enabled: bool,
+    // Minutes before the khutbah (or Dhuhr without khutbah) to leave for the masjid
+    leave_before: Option<i64>,
+    khutbah: Option<IqamaRule>,
+    iqama: Option<IqamaRule>,
+}
+impl Default for JumuahConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            leave_before: None,
+            khutbah: None,
+            iqama: None,
+        }
+    }
+}
+// Shell commands run by the daemon for each event
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+struct HooksConfig {
+    timeout: u64,
+    adhan: BTreeMap<Event, Vec<String>>,
+    reminder: BTreeMap<Event, Vec<String>>,
+    iqama: BTreeMap<Event, Vec<String>>,
+    missed: BTreeMap<Event, Vec<String>>,
+    departure: BTreeMap<Event, Vec<String>>,
+}
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            timeout: HOOK_TIMEOUT_SECONDS,
+            adhan: BTreeMap::new(),
+            reminder: BTreeMap::new(),
+            iqama: BTreeMap::new(),
+            missed: BTreeMap::new(),
+            departure: BTreeMap::new(),
+        }
+    }
+}
+// Sounds played by the daemon
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+struct AudioConfig {
+    enabled: bool,
+    // Command playing a file, given as last argument or in place of `{file}`
+    player: String,
+    // In percent
+    volume: u8,
+    // Stop the adhan when its notification is closed
+    stop_on_dismiss: bool,
+    fajr: Option<path::PathBuf>,
+    // Adhan of the other prayers, and of Fajr without its own file
+    adhan: Option<path::PathBuf>,
+    // Short chime for the reminders
+    reminder: Option<path::PathBuf>,
+}
+impl Default for AudioConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            player: AUDIO_PLAYER.to_string(),
+            volume: 100,
+            stop_on_dismiss: false,
+            fajr: None,
+            adhan: None,
+            reminder: None,
+        }
+    }
 }
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Config {
     location: Option<Location>,
+    // Shown in the notifications instead of the coordinates
+    #[serde(default)]
+    location_name: Option<String>,
     timezone: Option<String>,
+    // Language of the texts, from the environment when it is not set
+    #[serde(default)]
+    locale: Option<Locale>,
     prayer: PrayerConfig,
     notification: NotificationConfig,
+    #[serde(default)]
+    hijri: HijriConfig,
+    #[serde(default)]
+    jumuah: JumuahConfig,
+    #[serde(default)]
+    hooks: HooksConfig,
+    #[serde(default)]
+    audio: AudioConfig,
+    #[serde(default)]
+    quiet: QuietConfig,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             location: None,
+            location_name: None,
             timezone: None,
+            locale: None,
             prayer: PrayerConfig {
                 method: MethodVariant::default(),
                 madhab: Madhab::default(),
@@ -54,44 +265,106 @@ impl Default for Config {
                 asr_mod: 0,
                 maghrib_mod: 0,
                 isha_mod: 0,
+                high_latitude: HighLatitudeRule::default(),
+                iqama: BTreeMap::new(),
+                iqama_seasons: Vec::new(),
             },
             notification: NotificationConfig {
                 notify_before: false,
                 urgency: NotifUrgency::Critical,
                 icon: default_icon(),
-                interval: 20,
+                interval: None,
+                missed: MissedPolicy::default(),
+                missed_within: MISSED_WITHIN_MINUTES,
+                reminders: BTreeMap::new(),
+                notify_iqama: false,
+                templates: TemplatesConfig::default(),
+                styles: StylesConfig::default(),
+                events: BTreeMap::new(),
             },
+            hijri: HijriConfig::default(),
+            jumuah: JumuahConfig::default(),
+            hooks: HooksConfig::default(),
+            audio: AudioConfig::default(),
+            quiet: QuietConfig::default(),
         }
     }
 }
 
 impl Config {
+    // Read the toml config file
+    pub fn load() -> Result<Config, Error> {
+        let (program, config) = config_options();
+        confy::load::<Config>(program, config).map_err(|error| match error.source() {
+            Some(source) => {
+                PrayerError::ConfigParse(format!("{}, caused by: {}", error, source)).into()
+            }
+            None => PrayerError::ConfigParse(error.to_string()).into(),
+        })
+    }
+
     // Generate a new Config from command line arguments
-    pub fn new(args: &Arguments) -> Self {
-        // println!("{:?}", args);
+    pub fn new(args: &Arguments) -> Result<Self, Error> {
+        let config = Config::load().unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            Config::default()
+        });
+        Config::with_arguments(config, args)
+    }
 
-        // TODO: get prayer-times from
-        let (program, config) = config_options();
-        let config_res = confy::load::<Config>(program, config);
-        if let Err(error) = &config_res {
-            eprintln!("Error reading config file : {}", error);
-            eprintln!("Caused by: {}", error.source().unwrap());
+    // Read the config file again for the running daemon, an invalid file is an error here so that
+    // the daemon keeps its current config
+    pub fn reload(args: &Arguments) -> Result<Self, Error> {
+        Config::with_arguments(Config::load_existing(&config_file()?)?, args)
+    }
+
+    // Read a config file that must exist, confy would write the default config in its place
+    fn load_existing(file: &path::Path) -> Result<Config, Error> {
+        if !file.exists() {
+            return Err(Error::Io(format!(
+                "The config file {} doesn't exist",
+                file.display()
+            )));
         }
-        let config: Config = config_res.unwrap_or_default();
+        confy::load_path::<Config>(file).map_err(|error| match error.source() {
+            Some(source) => {
+                PrayerError::ConfigParse(format!("{}, caused by: {}", error, source)).into()
+            }
+            None => PrayerError::ConfigParse(error.to_string()).into(),
+        })
+    }
+
+    // Complete the config file with the command line arguments, they take precedence
+    fn with_arguments(config: Config, args: &Arguments) -> Result<Self, Error> {
+        config.validate_styles()?;
 
         let mut is_daemon = false;
         let mut interval = config.notification.interval;
         if let Some(Commands::Daemon(daemon)) = &args.command {
             is_daemon = true;
-            if daemon.interval.is_some() {
-                interval = daemon.interval.unwrap();
-            }
+            interval = daemon.interval.or(interval);
         }
-        if interval == 0 {
-            interval = 1;
+        if interval == Some(0) {
+            interval = Some(1);
             println!("Interval cannot be 0, setting it to 1 the minimum value");
         }
 
+        let mut audio = config.audio;
+        audio.enabled &= !args.no_audio;
+        if audio.volume > 100 {
+            audio.volume = 100;
+            eprintln!("Volume cannot exceed 100%, setting it to 100");
+        }
+
+        let mut hijri_adjustment = args.hijri_adjustment.unwrap_or(config.hijri.adjustment);
+        if hijri_adjustment.abs() > MAX_ADJUSTMENT {
+            hijri_adjustment = hijri_adjustment.clamp(-MAX_ADJUSTMENT, MAX_ADJUSTMENT);
+            eprintln!(
+                "Hijri adjustment cannot exceed {} days, setting it to {}",
+                MAX_ADJUSTMENT, hijri_adjustment
+            );
+        }
+
         let location: Location;
         if let (Some(latitude), Some(longitude)) = (args.latitude, args.longitude) {
             location = Location {
@@ -103,23 +376,37 @@ impl Config {
         } else if let Some(auto_location) = current_location(is_daemon) {
             location = auto_location;
         } else {
-            eprintln!("No location provided in arguments or config file and impossible to get it automatically");
-            eprintln!("Run the program using the latitude and longitude arguments or set them in the config file");
-            eprintln!("Example : {program} --latitude <LAT> --longitude <LON>");
-            std::process::exit(1);
+            return Err(PrayerError::LocationUnavailable.into());
         }
 
-        Self {
+        let mut timezone = args.timezone.clone().or(config.timezone);
+        if let Some(tz_str) = &timezone {
+            if tz_str.parse::<Tz>().is_err() {
+                eprintln!(
+                    "Invalid timezone '{}', falling back to system timezone {}",
+                    tz_str,
+                    system_timezone()
+                );
+                timezone = None;
+            }
+        }
+
+        Ok(Self {
             location: Some(location),
-            timezone: args.timezone.clone().or(config.timezone),
+            location_name: config.location_name,
+            timezone,
+            locale: args.locale.or(config.locale),
             prayer: PrayerConfig {
-                method: args.method.clone().unwrap_or(config.prayer.method),
+                method: args.method.unwrap_or(config.prayer.method),
                 madhab: args.madhab.clone().unwrap_or(config.prayer.madhab),
                 fajr_mod: args.fajr_mod.unwrap_or(config.prayer.fajr_mod),
                 dhuhr_mod: args.dhuhr_mod.unwrap_or(config.prayer.dhuhr_mod),
                 asr_mod: args.asr_mod.unwrap_or(config.prayer.asr_mod),
                 maghrib_mod: args.maghrib_mod.unwrap_or(config.prayer.maghrib_mod),
                 isha_mod: args.isha_mod.unwrap_or(config.prayer.isha_mod),
+                high_latitude: args.high_latitude.unwrap_or(config.prayer.high_latitude),
+                iqama: config.prayer.iqama,
+                iqama_seasons: config.prayer.iqama_seasons,
             },
             notification: NotificationConfig {
                 notify_before: args
@@ -128,96 +415,235 @@ impl Config {
                 icon: args.icon.clone().unwrap_or(config.notification.icon),
                 urgency: args.urgency.clone().unwrap_or(config.notification.urgency),
                 interval,
+                missed: args.missed.unwrap_or(config.notification.missed),
+                missed_within: args
+                    .missed_within
+                    .unwrap_or(config.notification.missed_within),
+                reminders: config.notification.reminders,
+                notify_iqama: args
+                    .notify_iqama
+                    .unwrap_or(config.notification.notify_iqama),
+                templates: config.notification.templates,
+                styles: config.notification.styles,
+                events: config.notification.events,
             },
-        }
+            hijri: HijriConfig {
+                adjustment: hijri_adjustment,
+            },
+            jumuah: config.jumuah,
+            hooks: config.hooks,
+            audio,
+            quiet: config.quiet,
+        })
     }
 
-    pub fn lat(&self) -> f64 {
-        if let Some(location) = &self.location {
-            return location.lat;
-        }
-        0.
+    pub fn timezone(&self) -> Tz {
+        self.fixed_timezone().unwrap_or_else(system_timezone)
     }
-    pub fn lon(&self) -> f64 {
-        if let Some(location) = &self.location {
-            return location.lon;
-        }
-        0.
+
+    // Timezone given in the config or the arguments, `None` to follow the system timezone
+    pub fn fixed_timezone(&self) -> Option<Tz> {
+        self.timezone
+            .as_ref()
+            .and_then(|tz_str| tz_str.parse().ok())
     }
 
-    pub fn timezone_offset(&self) -> i64 {
-        match &self.timezone {
-            Some(tz_str) => parse_timezone_string(tz_str),
-            None => system_timezone_offset(),
+    pub fn locale(&self) -> Locale {
+        self.locale.or_else(Locale::from_env).unwrap_or_default()
+    }
+
+    // Name of the location for the notifications, its coordinates without name
+    pub fn location_name(&self) -> String {
+        match (&self.location_name, &self.location) {
+            (Some(name), _) => name.clone(),
+            (None, Some(location)) => format!("{:.4}, {:.4}", location.lat, location.lon),
+            (None, None) => String::new(),
         }
     }
 
-    pub fn fajr_param(&self) -> ParamValue {
-        self.prayer.method.get().params.fajr
+    // Check the notification styles, a wrong style is only noticed when a notification is sent otherwise
+    fn validate_styles(&self) -> Result<(), Error> {
+        let styles = &self.notification.styles;
+        let kinds = [
+            ("adhan", &styles.adhan),
+            ("reminder", &styles.reminder),
+            ("iqama", &styles.iqama),
+            ("missed", &styles.missed),
+            ("departure", &styles.departure),
+        ]
+        .map(|(name, style)| (format!("styles.{}", name), style));
+        let events = self
+            .notification
+            .events
+            .iter()
+            .map(|(event, style)| (format!("events.{}", event), style));
+        for (name, style) in kinds.into_iter().chain(events) {
+            style.validate().map_err(|reason| {
+                Error::from(PrayerError::ConfigParse(format!(
+                    "[notification.{}] {}",
+                    name, reason
+                )))
+            })?;
+        }
+        Ok(())
     }
-    pub fn isha_param(&self) -> ParamValue {
-        self.prayer.method.get().params.isha
+
+    // Style of a notification, the reminders are discreet unless configured otherwise
+    pub fn style(&self, kind: NotificationKind, event: Event) -> NotificationStyle {
+        let styles = &self.notification.styles;
+        let (urgency, kind_style) = match kind {
+            NotificationKind::Adhan => (self.notification.urgency.clone(), &styles.adhan),
+            NotificationKind::Reminder => (NotifUrgency::Low, &styles.reminder),
+            NotificationKind::Iqama => (self.notification.urgency.clone(), &styles.iqama),
+            NotificationKind::Missed => (self.notification.urgency.clone(), &styles.missed),
+            NotificationKind::Departure => (self.notification.urgency.clone(), &styles.departure),
+        };
+        let style = NotificationStyle {
+            urgency: Some(urgency),
+            icon: Some(self.icon()),
+            ..Default::default()
+        }
+        .with(kind_style);
+        match (kind, self.notification.events.get(&event)) {
+            (NotificationKind::Adhan | NotificationKind::Missed, Some(event_style)) => {
+                style.with(event_style)
+            }
+            _ => style,
+        }
     }
-    pub fn shadow_multiplier(&self) -> u8 {
-        self.prayer.madhab.shadow_multiplier()
+
+    pub fn quiet(&self) -> &QuietConfig {
+        &self.quiet
     }
 
-    pub fn offset(&self, event: Event) -> f64 {
-        let minutes_mod = match event {
-            Event::Fajr => self.prayer.fajr_mod,
-            Event::Sunrise => 0,
-            Event::Dhuhr => self.prayer.dhuhr_mod,
-            Event::Asr => self.prayer.asr_mod,
-            Event::Sunset => 0,
-            Event::Maghrib => self.prayer.maghrib_mod,
-            Event::Isha => self.prayer.isha_mod,
-            Event::Midnight => 0,
-        };
-        minutes_mod as f64 / 60.
+    pub fn template(&self, kind: NotificationKind) -> &Template {
+        let templates = &self.notification.templates;
+        match kind {
+            NotificationKind::Adhan => &templates.adhan,
+            NotificationKind::Reminder => &templates.reminder,
+            NotificationKind::Iqama => &templates.iqama,
+            NotificationKind::Missed => &templates.missed,
+            NotificationKind::Departure => &templates.departure,
+        }
     }
 
-    pub fn notify_before(&self) -> bool {
-        self.notification.notify_before
+    // Parameters of the prayer times calculation
+    pub fn parameters(&self) -> Parameters {
+        Parameters {
+            location: self
+                .location
+                .clone()
+                .unwrap_or(Location { lat: 0., lon: 0. }),
+            timezone: self.timezone(),
+            method: self.prayer.method,
+            madhab: self.prayer.madhab.clone(),
+            high_latitude: self.prayer.high_latitude,
+            fajr_mod: self.prayer.fajr_mod,
+            dhuhr_mod: self.prayer.dhuhr_mod,
+            asr_mod: self.prayer.asr_mod,
+            maghrib_mod: self.prayer.maghrib_mod,
+            isha_mod: self.prayer.isha_mod,
+            hijri_adjustment: self.hijri.adjustment,
+            iqama: IqamaSchedule {
+                rules: self.prayer.iqama.clone(),
+                seasons: self.prayer.iqama_seasons.clone(),
+            },
+            jumuah: Jumuah {
+                enabled: self.jumuah.enabled,
+                khutbah: self.jumuah.khutbah,
+                iqama: self.jumuah.iqama,
+            },
+            locale: self.locale(),
+        }
     }
-    pub fn urgency(&self) -> Urgency {
-        // TODO : why do I need clone here
-        self.notification.urgency.clone().into()
+
+    // Minutes from each prayer to its reminders, `notify_before` is used for the prayers without
+    // reminders in the config
+    pub fn reminders(&self) -> BTreeMap<Event, Vec<i64>> {
+        Event::list()
+            .into_iter()
+            .map(|event| {
+                let mut minutes = match self.notification.reminders.get(&event) {
+                    Some(minutes) => minutes.clone(),
+                    None if self.notification.notify_before => vec![-NOTIFY_BEFORE_MINUTES],
+                    None => Vec::new(),
+                };
+                minutes.sort_unstable();
+                minutes.dedup();
+                (event, minutes)
+            })
+            .collect()
     }
     pub fn icon(&self) -> path::PathBuf {
         self.notification.icon.clone()
     }
-    pub fn interval(&self) -> u64 {
+    pub fn interval(&self) -> Option<u64> {
         self.notification.interval
     }
-}
-
-fn parse_timezone_string(tz_str: &str) -> i64 {
-    if let Ok(tz) = tz_str.parse::<chrono_tz::Tz>() {
-        // println!("Parsed timezone: {:?}", timezone_to_offset(tz));
-        return timezone_to_offset(tz);
+    pub fn notify_iqama(&self) -> bool {
+        self.notification.notify_iqama
+    }
+    // Minutes before the Friday prayer to leave for the masjid
+    pub fn jumuah_leave_before(&self) -> Option<i64> {
+        self.jumuah.leave_before.filter(|_| self.jumuah.enabled)
+    }
+    // Commands to run for an event
+    pub fn hooks(&self, kind: NotificationKind, event: Event) -> &[String] {
+        let hooks = match kind {
+            NotificationKind::Adhan => &self.hooks.adhan,
+            NotificationKind::Reminder => &self.hooks.reminder,
+            NotificationKind::Iqama => &self.hooks.iqama,
+            NotificationKind::Missed => &self.hooks.missed,
+            NotificationKind::Departure => &self.hooks.departure,
+        };
+        hooks.get(&event).map(Vec::as_slice).unwrap_or_default()
+    }
+    pub fn hook_timeout(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.hooks.timeout as i64)
+    }
+    pub fn audio_player(&self) -> &str {
+        &self.audio.player
+    }
+    pub fn audio_volume(&self) -> u8 {
+        self.audio.volume
+    }
+    pub fn stop_audio_on_dismiss(&self) -> bool {
+        self.audio.enabled && self.audio.stop_on_dismiss
+    }
+    // Sound played at the time of an event, the events that are not prayers get the chime
+    pub fn adhan_sound(&self, event: Event) -> Option<&path::Path> {
+        match event {
+            Event::Fajr => self.audio.fajr.as_ref().or(self.audio.adhan.as_ref()),
+            _ if has_adhan(event) => self.audio.adhan.as_ref(),
+            _ => self.audio.reminder.as_ref(),
+        }
+        .filter(|_| self.audio.enabled)
+        .map(path::PathBuf::as_path)
+    }
+    pub fn chime_sound(&self) -> Option<&path::Path> {
+        self.audio
+            .reminder
+            .as_deref()
+            .filter(|_| self.audio.enabled)
+    }
+    pub fn missed(&self) -> MissedPolicy {
+        self.notification.missed
+    }
+    pub fn missed_within(&self) -> chrono::Duration {
+        chrono::Duration::minutes(self.notification.missed_within as i64)
     }
-
-    let offset = system_timezone_offset();
-    eprintln!(
-        "Invalid timezone '{}', falling back to system timezone GMT{:+}",
-        tz_str, offset
-    );
-    offset
 }
 
-fn timezone_to_offset(timezone: chrono_tz::Tz) -> i64 {
-    let now_utc = Utc::now();
-    let local_time = timezone.from_utc_datetime(&now_utc.naive_utc());
-    let offset = local_time.offset();
-    let total_offset = offset.base_utc_offset() + offset.dst_offset();
-    total_offset.num_hours()
+// IANA timezone of the system, UTC if it cannot be found
+pub fn system_timezone() -> Tz {
+    iana_time_zone::get_timezone()
+        .ok()
+        .and_then(|tz_str| tz_str.parse().ok())
+        .unwrap_or(Tz::UTC)
 }
 
-fn system_timezone_offset() -> i64 {
-    let local_time = Local::now();
-    let offset = local_time.offset().local_minus_utc();
-    let offset_hours = offset / 3600;
-    offset_hours as i64
+fn default_missed_within() -> u64 {
+    MISSED_WITHIN_MINUTES
 }
 
 // Get the icon of the notification that should be sent
@@ -235,3 +661,87 @@ pub fn config_options<'a>() -> (&'a str, &'a str) {
     const PROGRAM_NAME: &str = env!("CARGO_PKG_NAME");
     (PROGRAM_NAME, "config")
 }
+
+// Path of the toml config file, it may not exist yet
+pub fn config_file() -> Result<path::PathBuf, Error> {
+    let (program, config) = config_options();
+    confy::get_configuration_file_path(program, config)
+        .map_err(|error| PrayerError::ConfigParse(error.to_string()).into())
+}
+
+// Default config with the keys of the toml `edits`, for the tests of the other modules
+#[cfg(test)]
+pub fn test_config(edits: &str) -> Config {
+    fn merge(table: &mut toml::Table, edits: toml::Table) {
+        for (key, value) in edits {
+            match (table.get_mut(&key), value) {
+                (Some(toml::Value::Table(table)), toml::Value::Table(edits)) => merge(table, edits),
+                (_, value) => {
+                    table.insert(key, value);
+                }
+            }
+        }
+    }
+    let mut config = toml::Table::try_from(Config::default()).unwrap();
+    merge(&mut config, toml::from_str(edits).unwrap());
+    config.try_into().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reload_needs_the_file() {
+        let directory =
+            std::env::temp_dir().join(format!("prayer-times-reload-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let file = directory.join("config.toml");
+
+        // A removed file is not written again
+        assert!(Config::load_existing(&file).is_err());
+        assert!(!file.exists());
+
+        std::fs::write(
+            &file,
+            toml::to_string(&test_config("locale = 'fr'")).unwrap(),
+        )
+        .unwrap();
+        let config = Config::load_existing(&file);
+        std::fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(config.unwrap().locale(), Locale::Fr);
+    }
+
+    #[test]
+    fn test_notification_styles() {
+        let mut config = Config::default();
+        config.notification.styles.reminder.timeout = Some(10);
+        config.notification.events.insert(
+            Event::Fajr,
+            NotificationStyle {
+                timeout: Some(0),
+                resident: Some(true),
+                ..Default::default()
+            },
+        );
+
+        let fajr = config.style(NotificationKind::Adhan, Event::Fajr);
+        assert_eq!(fajr.urgency, Some(NotifUrgency::Critical));
+        assert_eq!((fajr.timeout, fajr.resident), (Some(0), Some(true)));
+        // The style of the event is only for its adhan
+        let reminder = config.style(NotificationKind::Reminder, Event::Fajr);
+        assert_eq!(reminder.urgency, Some(NotifUrgency::Low));
+        assert_eq!((reminder.timeout, reminder.resident), (Some(10), None));
+        assert!(config.validate_styles().is_ok());
+
+        config
+            .notification
+            .events
+            .get_mut(&Event::Fajr)
+            .unwrap()
+            .transient = Some(true);
+        assert!(config.validate_styles().is_err());
+        config.notification.styles.adhan.category = Some("x prayer".to_string());
+        assert!(config.notification.styles.adhan.validate().is_err());
+    }
+}
@@ -1,82 +1,1395 @@
+use crate::app_error::Error;
 use crate::{
-    config::Config,
-    notification::{notify_before_prayer, notify_prayer},
-    prayer::Prayer,
-    prayers,
+    arguments::{parse_pause_end, Arguments},
+    audio::{AudioNotifier, CurrentPlayback},
+    clock_watch,
+    config::{self, system_timezone, Config},
+    dbus,
+    hooks::HookNotifier,
+    ipc::{self, Request, Response},
+    missed_policy::MissedPolicy,
+    notification::{
+        Action, DesktopNotifier, NotificationAction, NotificationKind, Notifier, Notifiers,
+        SNOOZE_MINUTES,
+    },
+    output, quiet, reload,
 };
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+use prayer_times::{prayers, Clock, Event, Parameters, Prayer};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::time::Instant;
 
-pub fn run_daemon(config: &Config) {
-    let mut daemon = PrayerDaemon::new(config);
+// Time before computing the next prayer again when it failed
+const RETRY_SECONDS: i64 = 60;
+// Difference between the wall clock and the monotonic clock over a sleep that is seen as a jump
+const JUMP_TOLERANCE_SECONDS: i64 = 5;
+// A prayer handled later than this after its time is missed
+const LATE_TOLERANCE_SECONDS: i64 = 60;
+// Missed prayers older than this are dropped when catching up
+const MAX_CATCH_UP_DAYS: i64 = 1;
+
+// Everything the daemon needs to know about the time of the system, replaced in tests.
+// `now` is the wall clock, it can jump when the system time changes
+pub trait DaemonClock: Clock {
+    // Monotonic clock, it doesn't move while the system is suspended
+    fn monotonic(&self) -> Duration;
+    fn timezone(&self) -> Tz;
+    fn sleep(&self, duration: Duration);
+    // Sleep, or less when a message comes meanwhile
+    fn sleep_until_message(
+        &self,
+        duration: Duration,
+        messages: &Receiver<Message>,
+    ) -> Option<Message> {
+        self.sleep(duration);
+        messages.try_recv().ok()
+    }
+}
+
+pub struct ProcessClock<'a> {
+    // Wall clock, possibly moved with `--now`
+    clock: &'a dyn Clock,
+    start: Instant,
+}
+
+impl Clock for ProcessClock<'_> {
+    fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+}
+
+impl DaemonClock for ProcessClock<'_> {
+    fn monotonic(&self) -> Duration {
+        // `Instant` uses CLOCK_MONOTONIC on Linux, which stops during a suspend
+        Duration::from_std(self.start.elapsed()).unwrap_or(Duration::MAX)
+    }
+    fn timezone(&self) -> Tz {
+        system_timezone()
+    }
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration.to_std().unwrap_or_default());
+    }
+    fn sleep_until_message(
+        &self,
+        duration: Duration,
+        messages: &Receiver<Message>,
+    ) -> Option<Message> {
+        match messages.recv_timeout(duration.to_std().unwrap_or_default()) {
+            Ok(message) => Some(message),
+            Err(RecvTimeoutError::Timeout) => None,
+            // Without notifications nor socket there is nothing to wait for
+            Err(RecvTimeoutError::Disconnected) => {
+                self.sleep(duration);
+                None
+            }
+        }
+    }
+}
+
+// What wakes the daemon up while it sleeps
+pub enum Message {
+    // Picked on a notification
+    Action(Box<NotificationAction>),
+    // Received on the socket, the response is sent back
+    Request(Request, Sender<Response>),
+    // Read the config again, for the given reason
+    Reload(&'static str),
+    // Look at the time again, for the given reason
+    Wake(&'static str),
+}
+
+// The config is read again from the file with the same `args`, on SIGHUP or when the file changes
+pub fn run_daemon(args: &Arguments, config: Config, clock: Box<dyn Clock>) -> Result<(), Error> {
+    // The audio starts before the notification that can stop it
+    let playback = CurrentPlayback::default();
+    let (sender, messages) = mpsc::channel();
+    // Before the other threads start
+    if let Err(error) = reload::watch_hangup(sender.clone()) {
+        eprintln!("{}", error);
+    }
+    match config::config_file() {
+        Ok(file) => reload::watch_file(file, sender.clone()),
+        Err(error) => eprintln!("{}", error),
+    }
+    // Without them, a suspend or a change of the time is only noticed at the next notification
+    if let Err(error) = clock_watch::watch_clock(sender.clone()) {
+        eprintln!("{}", error);
+    }
+    clock_watch::watch_timezone(sender.clone());
+    // The daemon still notifies without socket
+    if let Some(path) = ipc::socket_path() {
+        if let Err(error) = ipc::serve(&path, sender.clone()) {
+            eprintln!("{}", error);
+        }
+    }
+    let mut notifiers: Vec<Box<dyn Notifier>> = vec![
+        Box::new(AudioNotifier {
+            current: Arc::clone(&playback),
+        }),
+        Box::new(DesktopNotifier::new(
+            clock.as_ref(),
+            playback,
+            sender.clone(),
+        )),
+        Box::new(HookNotifier {
+            clock: clock.as_ref(),
+        }),
+    ];
+    // Nor without session bus
+    match dbus::serve(None, sender) {
+        Ok(notifier) => notifiers.push(Box::new(notifier)),
+        Err(error) => eprintln!("{}", error),
+    }
+    let notifier = Notifiers(notifiers);
+    let clock = ProcessClock {
+        clock: clock.as_ref(),
+        start: Instant::now(),
+    };
+    let load_config = Box::new(|| Config::reload(args));
+    let mut daemon = PrayerDaemon::new(config, load_config, clock, Box::new(notifier), messages);
     daemon.run();
+    Ok(())
+}
+
+// What the daemon has to do at a given moment
+#[derive(Debug, PartialEq)]
+enum Trigger {
+    // Index of the reminder in the pending reminders
+    Reminder(usize),
+    Prayer,
+}
+
+// Moment of a prayer instance a reminder is set from
+#[derive(Clone, Copy, PartialEq)]
+enum Anchor {
+    Adhan,
+    Iqama,
+    // Khutbah of the Friday prayer, or its adhan without khutbah time
+    Khutbah,
+}
+
+// Reminder of a prayer instance
+#[derive(Clone, PartialEq)]
+struct Reminder {
+    prayer: Prayer,
+    anchor: Anchor,
+    // From the anchor, negative before it
+    offset: Duration,
+}
+
+impl Reminder {
+    fn time(&self) -> DateTime<Utc> {
+        let anchor = match self.anchor {
+            Anchor::Adhan => None,
+            Anchor::Iqama => self.prayer.iqama(),
+            Anchor::Khutbah => self.prayer.khutbah(),
+        };
+        anchor.unwrap_or(self.prayer.date_time()).to_utc() + self.offset
+    }
+
+    // A late reminder is only useful before its prayer
+    fn is_relevant(&self, now: DateTime<Utc>) -> bool {
+        now - self.time() <= Duration::seconds(LATE_TOLERANCE_SECONDS)
+            || (self.offset < Duration::zero() && now < self.prayer.date_time())
+    }
 }
 
-struct PrayerDaemon<'a> {
-    config: &'a Config,
+struct PrayerDaemon<'a, C: DaemonClock> {
+    config: Config,
+    // Reads the config again, replaced in tests
+    load_config: Box<dyn Fn() -> Result<Config, Error> + 'a>,
+    clock: C,
+    notifier: Box<dyn Notifier + 'a>,
+    // Buttons picked on the notifications and requests of the socket
+    messages: Receiver<Message>,
+    parameters: Parameters,
     next_prayer: Prayer,
-    is_notified_before: bool,
+    // Minutes from each prayer to its reminders
+    reminder_minutes: BTreeMap<Event, Vec<i64>>,
+    notify_iqama: bool,
+    // Minutes before the khutbah to leave for the Friday prayer
+    jumuah_leave_before: Option<i64>,
+    // Reminders waiting for their time, and the ones already sent
+    reminders: Vec<Reminder>,
+    fired_reminders: Vec<Reminder>,
+    // Prayers marked as prayed from their notification, they get no more reminders
+    prayed: Vec<Prayer>,
+    // Written by the `pause` command
+    pause_file: PathBuf,
+    // Prayer of the last notification that can be snoozed
+    last_notified: Option<Prayer>,
 }
 
-impl<'a> PrayerDaemon<'a> {
-    fn new(config: &'a Config) -> Self {
-        Self {
+impl<'a, C: DaemonClock> PrayerDaemon<'a, C> {
+    fn new(
+        config: Config,
+        load_config: Box<dyn Fn() -> Result<Config, Error> + 'a>,
+        clock: C,
+        notifier: Box<dyn Notifier + 'a>,
+        messages: Receiver<Message>,
+    ) -> Self {
+        let mut parameters = config.parameters();
+        if config.fixed_timezone().is_none() {
+            parameters.timezone = clock.timezone();
+        }
+        let next_prayer = Self::first_prayer(&parameters, &clock);
+        let mut daemon = Self {
+            reminder_minutes: config.reminders(),
+            notify_iqama: config.notify_iqama(),
+            jumuah_leave_before: config.jumuah_leave_before(),
             config,
-            next_prayer: prayers::next(config),
-            is_notified_before: false,
+            load_config,
+            clock,
+            notifier,
+            messages,
+            parameters,
+            next_prayer: next_prayer.clone(),
+            reminders: Vec::new(),
+            fired_reminders: Vec::new(),
+            prayed: Vec::new(),
+            pause_file: quiet::pause_file(),
+            last_notified: None,
+        };
+        // The reminders after the current prayer can still be ahead
+        if let Ok(current) = next_prayer.previous() {
+            daemon.schedule_reminders(&current);
+        }
+        daemon.schedule_reminders(&next_prayer);
+        daemon
+    }
+
+    // The next prayer skips the events of a polar day or night, it only fails when none happens
+    // until tomorrow. It is retried after a sleep like after a passed prayer
+    fn first_prayer(parameters: &Parameters, clock: &C) -> Prayer {
+        loop {
+            match prayers::next(parameters, clock) {
+                Ok(prayer) => return prayer,
+                Err(error) => {
+                    eprintln!("Failed to get the next prayer: {}", error);
+                    clock.sleep(Duration::seconds(RETRY_SECONDS));
+                }
+            }
+        }
+    }
+
+    fn schedule_reminders(&mut self, prayer: &Prayer) {
+        if self.prayed.contains(prayer) {
+            return;
         }
+        let now = self.clock.now();
+        let minutes = self.reminder_minutes.get(&prayer.event());
+        let offsets = minutes.into_iter().flatten().map(|&minutes| Reminder {
+            prayer: prayer.clone(),
+            anchor: Anchor::Adhan,
+            offset: Duration::minutes(minutes),
+        });
+        let iqama = (self.notify_iqama && prayer.iqama().is_some()).then(|| Reminder {
+            prayer: prayer.clone(),
+            anchor: Anchor::Iqama,
+            offset: Duration::zero(),
+        });
+        let departure = self
+            .jumuah_leave_before
+            .filter(|_| prayer.is_jumuah())
+            .map(|minutes| Reminder {
+                prayer: prayer.clone(),
+                anchor: Anchor::Khutbah,
+                offset: Duration::minutes(-minutes),
+            });
+        for reminder in offsets.chain(iqama).chain(departure) {
+            if reminder.is_relevant(now)
+                && !self.reminders.contains(&reminder)
+                && !self.fired_reminders.contains(&reminder)
+            {
+                self.reminders.push(reminder);
+            }
+        }
+    }
+
+    // Read the config again and compute the prayers and the reminders with it. Nothing changes
+    // when the new config is invalid
+    fn reload(&mut self) -> Result<(), Error> {
+        let reloaded = (self.load_config)().and_then(|config| {
+            let mut parameters = config.parameters();
+            if config.fixed_timezone().is_none() {
+                parameters.timezone = self.clock.timezone();
+            }
+            let next_prayer = prayers::next(&parameters, &self.clock)?;
+            Ok((config, parameters, next_prayer))
+        });
+        let (config, parameters, next_prayer) = match reloaded {
+            Ok(reloaded) => reloaded,
+            Err(error) => {
+                eprintln!("Keeping the current config: {}", error);
+                return Err(error);
+            }
+        };
+        println!("Reloaded the config");
+        self.reminder_minutes = config.reminders();
+        self.notify_iqama = config.notify_iqama();
+        self.jumuah_leave_before = config.jumuah_leave_before();
+        self.config = config;
+        self.parameters = parameters;
+        self.reminders.clear();
+        if let Ok(current) = next_prayer.previous() {
+            self.schedule_reminders(&current);
+        }
+        self.set_next_prayer(next_prayer, None);
+        self.print_info();
+        Ok(())
+    }
+
+    // `reached` is the prayer whose time came, when it is why the next prayer changes
+    fn set_next_prayer(&mut self, prayer: Prayer, reached: Option<Prayer>) {
+        self.schedule_reminders(&prayer);
+        if let Err(error) = self.notifier.next_prayer(&prayer, reached.as_ref()) {
+            eprintln!("{}", error);
+        }
+        self.next_prayer = prayer;
     }
 
     pub fn run(&mut self) {
         println!("Starting Prayer Times Background Process");
+        self.print_info();
         loop {
-            self.print_info();
+            self.step();
+        }
+    }
 
-            if self.next_prayer.time_has_passed() {
+    // Handle the trigger that is due, or sleep until the next one
+    fn step(&mut self) {
+        while let Ok(message) = self.messages.try_recv() {
+            self.handle_message(message);
+        }
+        self.check_timezone();
+        match self.due_trigger() {
+            Some(Trigger::Prayer) => {
                 self.handle_passed_prayer();
-            } else if self.should_notify_before() {
-                self.notify_before_prayer();
+                self.print_info();
             }
-
-            self.sleep();
+            Some(Trigger::Reminder(index)) => self.handle_reminder(index),
+            None => self.sleep(),
         }
     }
 
     fn print_info(&self) {
-        println!("{}", self.next_prayer.text_duration());
         println!("{}", self.next_prayer.text_time());
+        let next_due = self.next_due().with_timezone(&self.parameters.timezone);
+        println!(
+            "Next notification at {}",
+            next_due.format("%Y-%m-%d %H:%M:%S")
+        );
+    }
+
+    // Earliest trigger whose time has come
+    fn due_trigger(&self) -> Option<Trigger> {
+        let now = self.clock.now();
+        let prayer_time = self.next_prayer.date_time().to_utc();
+        let reminder = self
+            .reminders
+            .iter()
+            .enumerate()
+            .filter(|(_, reminder)| reminder.time() <= now)
+            .min_by_key(|(_, reminder)| reminder.time());
+        match reminder {
+            Some((index, reminder)) if reminder.time() < prayer_time || now < prayer_time => {
+                Some(Trigger::Reminder(index))
+            }
+            _ if now >= prayer_time => Some(Trigger::Prayer),
+            _ => None,
+        }
+    }
+
+    // Moment of the next trigger
+    fn next_due(&self) -> DateTime<Utc> {
+        self.reminders
+            .iter()
+            .map(Reminder::time)
+            .chain([self.next_prayer.date_time().to_utc()])
+            .min()
+            .unwrap_or_else(|| self.next_prayer.date_time().to_utc())
+    }
+
+    // Follow the changes of the system timezone, unless a timezone is configured
+    fn check_timezone(&mut self) {
+        if self.config.fixed_timezone().is_some() {
+            return;
+        }
+        let timezone = self.clock.timezone();
+        if timezone != self.parameters.timezone {
+            println!(
+                "Timezone changed from {} to {}",
+                self.parameters.timezone, timezone
+            );
+            self.parameters.timezone = timezone;
+            // The pending reminders were computed with the previous timezone
+            self.reminders.clear();
+            self.reschedule();
+        }
+    }
+
+    // Compute the next prayer again after a jump of the wall clock or a change of timezone
+    fn reschedule(&mut self) {
+        let now = self.clock.now();
+        let prayer_time = self.next_prayer.date_time().to_utc();
+        // Prayers missed during a suspend are still handled one by one, up to a limit
+        let start = if prayer_time <= now {
+            prayer_time.max(now - Duration::days(MAX_CATCH_UP_DAYS))
+        } else {
+            now
+        };
+        match prayers::next_at(&self.parameters, start) {
+            Ok(prayer) => {
+                self.set_next_prayer(prayer, None);
+                self.print_info();
+            }
+            Err(error) => eprintln!("Failed to get the next prayer: {}", error),
+        }
+    }
+
+    // Whether a prayer that is `lateness` late is notified
+    fn should_notify_missed(&self, lateness: Duration) -> bool {
+        match self.config.missed() {
+            MissedPolicy::Skip => false,
+            MissedPolicy::Notify => true,
+            MissedPolicy::NotifyWithin => lateness <= self.config.missed_within(),
+        }
     }
 
     fn handle_passed_prayer(&mut self) {
+        let now = self.clock.now();
         println!("Prayer time has passed");
-        // Notification only if it's the current prayer.
-        // If it's not the current prayer, it means that the system have been suspended
-        // so we are currently in an other prayer
-        if self.next_prayer == prayers::current(self.config) {
-            notify_prayer(&self.next_prayer, self.config);
+
+        let following = match self.next_prayer.next() {
+            Ok(prayer) => prayer,
+            Err(error) => {
+                // The chain of prayers is broken (polar day or night), start again from now
+                eprintln!(
+                    "Failed to get the prayer after {}: {}",
+                    self.next_prayer.event(),
+                    error
+                );
+                match prayers::next_at(&self.parameters, now + Duration::seconds(1)) {
+                    Ok(prayer) => prayer,
+                    Err(error) => {
+                        // It will be retried after the next sleep
+                        eprintln!("Failed to get the next prayer: {}", error);
+                        self.clock.sleep(Duration::seconds(RETRY_SECONDS));
+                        return;
+                    }
+                }
+            }
+        };
+
+        let lateness = now - self.next_prayer.date_time().to_utc();
+        if lateness <= Duration::seconds(LATE_TOLERANCE_SECONDS) {
+            if !self.is_suppressed(NotificationKind::Adhan, &self.next_prayer) {
+                self.last_notified = Some(self.next_prayer.clone());
+                if let Err(error) = self.notifier.prayer(&self.next_prayer, &self.config) {
+                    eprintln!("{}", error);
+                }
+            }
+        } else {
+            println!(
+                "Missed {} by {} minutes",
+                self.next_prayer.name(),
+                lateness.num_minutes()
+            );
+            // After a long suspend, only the latest of the missed prayers is notified
+            let superseded = following.date_time().to_utc() <= now;
+            if self.should_notify_missed(lateness)
+                && !superseded
+                && !self.prayed.contains(&self.next_prayer)
+                && !self.is_suppressed(NotificationKind::Missed, &self.next_prayer)
+            {
+                self.last_notified = Some(self.next_prayer.clone());
+                if let Err(error) = self.notifier.missed_prayer(&self.next_prayer, &self.config) {
+                    eprintln!("{}", error);
+                }
+            }
+        }
+
+        let reached = self.next_prayer.clone();
+        self.set_next_prayer(following, Some(reached));
+    }
+
+    fn handle_reminder(&mut self, index: usize) {
+        let now = self.clock.now();
+        let reminder = self.reminders.remove(index);
+        let kind = match reminder.anchor {
+            Anchor::Adhan => NotificationKind::Reminder,
+            Anchor::Iqama => NotificationKind::Iqama,
+            Anchor::Khutbah => NotificationKind::Departure,
+        };
+        if reminder.is_relevant(now) {
+            let offset = now - reminder.prayer.date_time().to_utc();
+            let result = match kind {
+                _ if self.is_suppressed(kind, &reminder.prayer) => Ok(()),
+                NotificationKind::Iqama => self.notifier.iqama(&reminder.prayer, &self.config),
+                NotificationKind::Departure => {
+                    self.notifier.departure(&reminder.prayer, &self.config)
+                }
+                _ => {
+                    self.last_notified = Some(reminder.prayer.clone());
+                    self.notifier
+                        .reminder(&reminder.prayer, offset, &self.config)
+                }
+            };
+            if let Err(error) = result {
+                eprintln!("{}", error);
+            }
+        } else {
+            println!(
+                "Skipped the reminder of {} at {}",
+                reminder.prayer.event(),
+                reminder.time().with_timezone(&self.parameters.timezone)
+            );
+        }
+
+        // Only the recent reminders can be scheduled again
+        let oldest = now - Duration::days(MAX_CATCH_UP_DAYS + 1);
+        self.fired_reminders
+            .retain(|fired| fired.prayer.date_time() >= oldest);
+        self.fired_reminders.push(reminder);
+    }
+
+    // Send the notification of the prayer again later, as a reminder
+    fn snooze(&mut self, prayer: Prayer) {
+        let time = self.clock.now() + Duration::minutes(SNOOZE_MINUTES);
+        println!(
+            "Snoozed {} until {}",
+            prayer.name(),
+            time.with_timezone(&self.parameters.timezone)
+                .format("%H:%M:%S")
+        );
+        self.reminders.push(Reminder {
+            offset: time - prayer.date_time().to_utc(),
+            prayer,
+            anchor: Anchor::Adhan,
+        });
+    }
+
+    // Whether the quiet rules or a pause prevent a notification, it is logged instead
+    fn is_suppressed(&self, kind: NotificationKind, prayer: &Prayer) -> bool {
+        let now = self.clock.now();
+        let moment = now.with_timezone(&self.parameters.timezone).naive_local();
+        let paused_until = quiet::paused_until(&self.pause_file, now);
+        let suppression =
+            self.config
+                .quiet()
+                .suppression(kind, prayer.event(), moment, paused_until);
+        if let Some(reason) = &suppression {
+            println!(
+                "Suppressed the {} notification of {}: {}",
+                kind,
+                prayer.name(),
+                reason
+            );
+        }
+        suppression.is_some()
+    }
+
+    fn handle_message(&mut self, message: Message) {
+        match message {
+            Message::Action(action) => self.handle_action(*action),
+            Message::Request(request, sender) => {
+                let response = Response::from(self.handle_request(request));
+                // The client may have given up waiting
+                let _ = sender.send(response);
+            }
+            Message::Reload(reason) => {
+                println!("Reloading the config: {}", reason);
+                // The error is already reported
+                let _ = self.reload();
+            }
+            // The jump of the time or the timezone is handled after the sleep
+            Message::Wake(reason) => println!("Woken up: {}", reason),
+        }
+    }
+
+    fn handle_request(&mut self, request: Request) -> Result<Response, Error> {
+        let clock = &self.clock;
+        match request {
+            // Computed like the commands without daemon, the answer is the same
+            Request::Next => {
+                let prayer = prayers::next(&self.parameters, clock)?;
+                let value = output::to_value(&prayer, &self.parameters, clock)?;
+                Ok(Response::new(prayer.text_duration(clock), vec![value]))
+            }
+            Request::Current => {
+                let prayer = prayers::current(&self.parameters, clock)?;
+                let value = output::to_value(&prayer, &self.parameters, clock)?;
+                Ok(Response::new(prayer.text_time(), vec![value]))
+            }
+            Request::Today => {
+                let prayers = prayers::list_prayers(&self.parameters, clock);
+                let text = output::prayers_text(&prayers, &self.parameters);
+                let values = output::to_values(prayers, &self.parameters, clock)?;
+                Ok(Response::new(text, values))
+            }
+            Request::Status => Ok(self.status()),
+            Request::Snooze => {
+                let prayer = self.last_notified.clone().ok_or(Error::NothingToSnooze)?;
+                self.snooze(prayer);
+                Ok(self.status())
+            }
+            Request::Pause { duration } => {
+                let until = parse_pause_end(clock.now(), &duration)?;
+                quiet::pause(&self.pause_file, until)?;
+                println!(
+                    "Paused until {}",
+                    until
+                        .with_timezone(&self.parameters.timezone)
+                        .format("%Y-%m-%d %H:%M")
+                );
+                Ok(self.status())
+            }
+            Request::Resume => {
+                quiet::resume(&self.pause_file)?;
+                println!("Resumed the notifications");
+                Ok(self.status())
+            }
+            Request::Reload => {
+                self.reload()?;
+                Ok(self.status())
+            }
+        }
+    }
+
+    // Next prayer and notification, and the end of the pause
+    fn status(&self) -> Response {
+        let timezone = self.parameters.timezone;
+        let next_notification = self.next_due().with_timezone(&timezone);
+        let paused_until = quiet::paused_until(&self.pause_file, self.clock.now())
+            .map(|until| until.with_timezone(&timezone));
+        let mut lines = vec![
+            self.next_prayer.text_time(),
+            format!(
+                "Next notification at {}",
+                next_notification.format("%Y-%m-%d %H:%M:%S")
+            ),
+        ];
+        if let Some(until) = paused_until {
+            lines.push(format!("Paused until {}", until.format("%Y-%m-%d %H:%M")));
+        }
+        let value = serde_json::json!({
+            "next_event": self.next_prayer.event(),
+            "next_name": self.next_prayer.name(),
+            "next_time": self.next_prayer.date_time(),
+            "next_notification": next_notification,
+            "pending_reminders": self.reminders.len(),
+            "paused_until": paused_until,
+            "timezone": timezone.name(),
+        });
+        Response::new(lines.join("\n"), vec![value])
+    }
+
+    fn handle_action(&mut self, NotificationAction { prayer, action }: NotificationAction) {
+        match action {
+            Action::Snooze => self.snooze(prayer),
+            Action::Dismiss => println!("Dismissed the notification of {}", prayer.name()),
+            Action::Prayed => {
+                println!("Marked {} as prayed", prayer.name());
+                self.reminders.retain(|reminder| reminder.prayer != prayer);
+                let oldest = self.clock.now() - Duration::days(MAX_CATCH_UP_DAYS + 1);
+                self.prayed.retain(|prayed| prayed.date_time() >= oldest);
+                self.prayed.push(prayer);
+            }
+        }
+    }
+
+    // Sleep until the next trigger, or at most `interval`. A jump of the wall clock wakes the
+    // daemon up with a message from `clock_watch`
+    fn sleep(&mut self) {
+        let (wall_start, monotonic_start) = (self.clock.now(), self.clock.monotonic());
+        let mut duration = self.next_due() - wall_start;
+        if let Some(interval) = self.config.interval() {
+            duration = duration.min(Duration::seconds(interval as i64));
+        }
+        let message = self.clock.sleep_until_message(duration, &self.messages);
+
+        // Both clocks move together, unless the system was suspended or its time changed
+        let divergence =
+            (self.clock.now() - wall_start) - (self.clock.monotonic() - monotonic_start);
+        if divergence.abs() > Duration::seconds(JUMP_TOLERANCE_SECONDS) {
+            println!(
+                "System time jumped by {} seconds (suspend or time change)",
+                divergence.num_seconds()
+            );
+            self.reschedule();
+        }
+        if let Some(message) = message {
+            self.handle_message(message);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{test_config, NOTIFY_BEFORE_MINUTES};
+    use crate::notification::{departure_summary, reminder_summary};
+    use chrono::TimeZone;
+    use prayer_times::{Event, FixedClock, Locale};
+    use std::cell::{Cell, RefCell};
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+    use std::rc::Rc;
+
+    // Clock that only moves forward when the daemon sleeps
+    struct FakeClock {
+        now: Cell<DateTime<Utc>>,
+        monotonic: Cell<Duration>,
+        timezone: Cell<Tz>,
+        // Added to the wall clock during the next sleep, like a suspend or a change of time
+        jump: Cell<Duration>,
+        sleeps: RefCell<Vec<Duration>>,
+    }
+
+    impl Clock for &FakeClock {
+        fn now(&self) -> DateTime<Utc> {
+            self.now.get()
+        }
+    }
+
+    impl DaemonClock for &FakeClock {
+        fn monotonic(&self) -> Duration {
+            self.monotonic.get()
+        }
+        fn timezone(&self) -> Tz {
+            self.timezone.get()
+        }
+        fn sleep(&self, duration: Duration) {
+            self.sleeps.borrow_mut().push(duration);
+            self.now.set(self.now.get() + duration + self.jump.take());
+            self.monotonic.set(self.monotonic.get() + duration);
+        }
+        // A message sent before the sleep wakes it up right away
+        fn sleep_until_message(
+            &self,
+            duration: Duration,
+            messages: &Receiver<Message>,
+        ) -> Option<Message> {
+            let message = messages.try_recv().ok();
+            self.sleep(match message {
+                Some(_) => Duration::zero(),
+                None => duration,
+            });
+            message
+        }
+    }
+
+    // Keeps the notifications instead of sending them, and the changes of the next prayer
+    #[derive(Default)]
+    struct RecordingNotifier {
+        sent: Rc<RefCell<Vec<String>>>,
+        signals: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Notifier for RecordingNotifier {
+        fn prayer(&self, prayer: &Prayer, _config: &Config) -> Result<(), Error> {
+            self.sent.borrow_mut().push(prayer.name().to_string());
+            Ok(())
+        }
+        fn reminder(
+            &self,
+            prayer: &Prayer,
+            offset: Duration,
+            _config: &Config,
+        ) -> Result<(), Error> {
+            self.sent.borrow_mut().push(reminder_summary(
+                Locale::En,
+                prayer.name(),
+                offset.num_minutes(),
+            ));
+            Ok(())
+        }
+        fn missed_prayer(&self, prayer: &Prayer, _config: &Config) -> Result<(), Error> {
+            self.sent
+                .borrow_mut()
+                .push(format!("Missed {}", prayer.name()));
+            Ok(())
+        }
+        fn iqama(&self, prayer: &Prayer, _config: &Config) -> Result<(), Error> {
+            self.sent
+                .borrow_mut()
+                .push(format!("Iqama {}", prayer.name()));
+            Ok(())
+        }
+        fn departure(&self, prayer: &Prayer, _config: &Config) -> Result<(), Error> {
+            self.sent
+                .borrow_mut()
+                .push(departure_summary(prayer, Locale::En));
+            Ok(())
+        }
+        fn next_prayer(&self, next: &Prayer, reached: Option<&Prayer>) -> Result<(), Error> {
+            let reached = reached.map(|prayer| format!("{} reached, ", prayer.name()));
+            self.signals.borrow_mut().push(format!(
+                "{}next {}",
+                reached.unwrap_or_default(),
+                next.name()
+            ));
+            Ok(())
+        }
+    }
+
+    fn fake_clock() -> FakeClock {
+        FakeClock {
+            now: Cell::new(Utc.with_ymd_and_hms(2026, 3, 19, 10, 0, 0).unwrap()),
+            monotonic: Cell::new(Duration::zero()),
+            timezone: Cell::new(chrono_tz::Europe::Paris),
+            jump: Cell::new(Duration::zero()),
+            sleeps: RefCell::new(Vec::new()),
+        }
+    }
+
+    // The reminders 10 minutes before every prayer
+    const NOTIFY_BEFORE: &str = "notification = { notify_before = true }";
+
+    // Daemon in Paris waiting for Dhuhr, on a Thursday unless the clock was moved, with the config
+    // `edits`. Its timezone is the one of the clock
+    fn paris_daemon<'a>(
+        edits: &str,
+        clock: &'a FakeClock,
+        notifier: RecordingNotifier,
+    ) -> PrayerDaemon<'a, &'a FakeClock> {
+        let config = test_config(&format!(
+            "location = {{ lat = 48.8566, lon = 2.3522 }}\n{}",
+            edits
+        ));
+        let mut daemon = PrayerDaemon::new(
+            config,
+            Box::new(|| Ok(Config::default())),
+            clock,
+            Box::new(notifier),
+            mpsc::channel().1,
+        );
+        // Not the pause of the user running the tests
+        daemon.pause_file = PathBuf::new();
+        assert_eq!(daemon.next_prayer.event(), Event::Dhuhr);
+        daemon
+    }
+
+    #[test]
+    fn test_sleeps_until_the_notifications() {
+        let clock = fake_clock();
+        let mut daemon = paris_daemon(NOTIFY_BEFORE, &clock, RecordingNotifier::default());
+        let dhuhr_time = daemon.next_prayer.date_time().to_utc();
+
+        // Wakes up exactly at the notification before the prayer
+        while daemon.due_trigger().is_none() {
+            daemon.step();
+        }
+        assert_eq!(daemon.due_trigger(), Some(Trigger::Reminder(0)));
+        assert_eq!(
+            clock.now.get(),
+            dhuhr_time - Duration::minutes(NOTIFY_BEFORE_MINUTES)
+        );
+        assert_eq!(clock.sleeps.borrow().len(), 1);
+        daemon.step();
+        assert!(daemon.reminders.is_empty());
+        assert_eq!(daemon.fired_reminders.len(), 1);
+
+        // Then exactly at the prayer
+        while daemon.due_trigger().is_none() {
+            daemon.step();
+        }
+        assert_eq!(daemon.due_trigger(), Some(Trigger::Prayer));
+        assert_eq!(clock.now.get(), dhuhr_time);
+    }
+
+    #[test]
+    fn test_interval_caps_the_sleeps() {
+        let clock = fake_clock();
+        let mut daemon = paris_daemon(
+            "notification = { interval = 600 }",
+            &clock,
+            RecordingNotifier::default(),
+        );
+        let dhuhr_time = daemon.next_prayer.date_time().to_utc();
+
+        while daemon.due_trigger().is_none() {
+            daemon.step();
+        }
+        assert_eq!(clock.now.get(), dhuhr_time);
+        let sleeps = clock.sleeps.borrow();
+        assert!(sleeps.len() > 1);
+        assert!(sleeps.iter().all(|sleep| *sleep <= Duration::seconds(600)));
+    }
+
+    #[test]
+    fn test_wake_message_handles_the_jump() {
+        let clock = fake_clock();
+        let (sender, messages) = mpsc::channel();
+        let mut daemon = paris_daemon("", &clock, RecordingNotifier::default());
+        daemon.messages = messages;
+
+        // The time is set a day later while the daemon sleeps until Dhuhr
+        clock.jump.set(Duration::days(1));
+        sender.send(Message::Wake("system time changed")).unwrap();
+        daemon.sleep();
+        assert_eq!(*clock.sleeps.borrow(), [Duration::zero()]);
+        // The missed Dhuhr is handled right away
+        assert_eq!(daemon.next_prayer.event(), Event::Dhuhr);
+        assert_eq!(daemon.due_trigger(), Some(Trigger::Prayer));
+    }
+
+    #[test]
+    fn test_suspend_past_the_prayer() {
+        let clock = fake_clock();
+        let notifier = RecordingNotifier::default();
+        let sent = Rc::clone(&notifier.sent);
+        let mut daemon = paris_daemon(NOTIFY_BEFORE, &clock, notifier);
+        let dhuhr_time = daemon.next_prayer.date_time().to_utc();
+
+        clock.jump.set(Duration::hours(5));
+        daemon.step();
+        // The reminder before the prayer is too late and skipped
+        assert_eq!(daemon.due_trigger(), Some(Trigger::Reminder(0)));
+        daemon.step();
+        assert!(sent.borrow().is_empty());
+        // The missed prayer is still the next one to handle
+        assert_eq!(daemon.next_prayer.event(), Event::Dhuhr);
+        assert_eq!(daemon.due_trigger(), Some(Trigger::Prayer));
+
+        let lateness = clock.now.get() - dhuhr_time;
+        assert!(lateness > Duration::seconds(LATE_TOLERANCE_SECONDS));
+        assert!(!daemon.should_notify_missed(lateness));
+        assert!(daemon.should_notify_missed(Duration::minutes(10)));
+    }
+
+    #[test]
+    fn test_long_suspend_notifies_the_latest_missed_prayer() {
+        let clock = fake_clock();
+        let notifier = RecordingNotifier::default();
+        let sent = Rc::clone(&notifier.sent);
+        let mut daemon = paris_daemon("notification = { missed = 'Notify' }", &clock, notifier);
+
+        // Until 17:00 the next day, after Asr
+        clock.jump.set(Duration::hours(30));
+        daemon.step();
+        while daemon.due_trigger().is_some() {
+            daemon.step();
+        }
+        assert_eq!(*sent.borrow(), ["Missed Asr"]);
+        assert_eq!(daemon.next_prayer.event(), Event::Maghrib);
+    }
+
+    #[test]
+    fn test_backward_jump_reschedules() {
+        let clock = fake_clock();
+        let mut daemon = paris_daemon(NOTIFY_BEFORE, &clock, RecordingNotifier::default());
+
+        clock.jump.set(Duration::days(-1));
+        daemon.step();
+        assert_eq!(daemon.due_trigger(), None);
+        assert_eq!(
+            daemon.next_prayer.date_time().date_naive(),
+            chrono::NaiveDate::from_ymd_opt(2026, 3, 18).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_follows_system_timezone() {
+        let clock = fake_clock();
+        let mut daemon = paris_daemon(NOTIFY_BEFORE, &clock, RecordingNotifier::default());
+
+        clock.timezone.set(chrono_tz::Asia::Tokyo);
+        daemon.step();
+        assert_eq!(daemon.parameters.timezone, chrono_tz::Asia::Tokyo);
+        assert_eq!(
+            daemon.next_prayer.date_time().timezone(),
+            chrono_tz::Asia::Tokyo
+        );
+    }
+
+    #[test]
+    fn test_fast_forward_through_a_day() {
+        let clock = fake_clock();
+        let notifier = RecordingNotifier::default();
+        let sent = Rc::clone(&notifier.sent);
+        let mut daemon = paris_daemon(NOTIFY_BEFORE, &clock, notifier);
+
+        let end = clock.now.get() + Duration::days(1);
+        while clock.now.get() < end {
+            daemon.step();
+        }
+        assert_eq!(
+            *sent.borrow(),
+            [
+                "Adhan Dhuhr in 10 minutes",
+                "Dhuhr",
+                "Adhan Asr in 10 minutes",
+                "Asr",
+                "Adhan Maghrib in 10 minutes",
+                "Maghrib",
+                "Adhan Isha in 10 minutes",
+                "Isha",
+                "Adhan Midnight in 10 minutes",
+                "Midnight",
+                "Adhan Fajr in 10 minutes",
+                "Fajr",
+                "Adhan Sunrise in 10 minutes",
+                "Sunrise",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reminders_before_and_after_the_prayers() {
+        let clock = fake_clock();
+        let notifier = RecordingNotifier::default();
+        let sent = Rc::clone(&notifier.sent);
+        let edits = "notification.reminders = { Dhuhr = [-30, -10, 20], Asr = [-15] }";
+        let mut daemon = paris_daemon(edits, &clock, notifier);
+
+        let end = clock.now.get() + Duration::hours(9);
+        while clock.now.get() < end {
+            daemon.step();
+        }
+        assert_eq!(
+            *sent.borrow(),
+            [
+                "Adhan Dhuhr in 30 minutes",
+                "Adhan Dhuhr in 10 minutes",
+                "Dhuhr",
+                "Dhuhr started 20 minutes ago",
+                "Adhan Asr in 15 minutes",
+                "Asr",
+                "Maghrib",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_iqama_notifications() {
+        let clock = fake_clock();
+        let notifier = RecordingNotifier::default();
+        let sent = Rc::clone(&notifier.sent);
+        let edits = r#"
+            [notification]
+            notify_before = true
+            notify_iqama = true
+            [prayer.iqama]
+            Dhuhr = { rule = "After", minutes = 15 }
+            Asr = { rule = "After", minutes = 5 }
+            "#;
+        let mut daemon = paris_daemon(edits, &clock, notifier);
+
+        let end = clock.now.get() + Duration::hours(7);
+        while clock.now.get() < end {
+            daemon.step();
+        }
+        assert_eq!(
+            *sent.borrow(),
+            [
+                "Adhan Dhuhr in 10 minutes",
+                "Dhuhr",
+                "Iqama Dhuhr",
+                "Adhan Asr in 10 minutes",
+                "Asr",
+                "Iqama Asr",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_leave_for_jumuah() {
+        let clock = fake_clock();
+        clock
+            .now
+            .set(Utc.with_ymd_and_hms(2026, 3, 20, 10, 0, 0).unwrap());
+        let notifier = RecordingNotifier::default();
+        let sent = Rc::clone(&notifier.sent);
+        let edits = r#"
+            notification = { notify_before = true }
+            jumuah = { leave_before = 45, khutbah = { rule = "At", time = "13:30" } }
+            "#;
+        let mut daemon = paris_daemon(edits, &clock, notifier);
+        assert!(daemon.next_prayer.is_jumuah());
+
+        let end = clock.now.get() + Duration::hours(3);
+        while clock.now.get() < end {
+            daemon.step();
+        }
+        assert_eq!(
+            sent.borrow()[..3],
+            [
+                "Time to leave for Jumu'ah, khutbah at 13:30",
+                "Adhan Jumu'ah in 10 minutes",
+                "Jumu'ah",
+            ]
+        );
+        // Only on Fridays
+        let saturday = chrono::NaiveDate::from_ymd_opt(2026, 3, 21).unwrap();
+        let dhuhr = Prayer::new(Event::Dhuhr, saturday, &daemon.parameters).unwrap();
+        assert!(!dhuhr.is_jumuah());
+    }
+
+    #[test]
+    fn test_snooze_and_prayed_actions() {
+        let clock = fake_clock();
+        let notifier = RecordingNotifier::default();
+        let sent = Rc::clone(&notifier.sent);
+        let edits = "notification.reminders = { Dhuhr = [-20, 30] }";
+        let mut daemon = paris_daemon(edits, &clock, notifier);
+        let (sender, messages) = mpsc::channel();
+        daemon.messages = messages;
+        let dhuhr = daemon.next_prayer.clone();
+
+        while sent.borrow().is_empty() {
+            daemon.step();
+        }
+        // Snoozed from the reminder 20 minutes before the adhan
+        sender
+            .send(Message::Action(Box::new(NotificationAction {
+                prayer: dhuhr.clone(),
+                action: Action::Snooze,
+            })))
+            .unwrap();
+        while sent.borrow().len() < 3 {
+            daemon.step();
+        }
+        assert_eq!(
+            *sent.borrow(),
+            [
+                "Adhan Dhuhr in 20 minutes",
+                "Adhan Dhuhr in 15 minutes",
+                "Dhuhr"
+            ]
+        );
+
+        // No reminder 30 minutes after the adhan once prayed
+        sender
+            .send(Message::Action(Box::new(NotificationAction {
+                prayer: dhuhr,
+                action: Action::Prayed,
+            })))
+            .unwrap();
+        let end = clock.now.get() + Duration::hours(1);
+        while clock.now.get() < end {
+            daemon.step();
+        }
+        assert_eq!(sent.borrow().len(), 3);
+    }
+
+    #[test]
+    fn test_paused_notifications() {
+        let clock = fake_clock();
+        let notifier = RecordingNotifier::default();
+        let sent = Rc::clone(&notifier.sent);
+        let edits = "notification.reminders = { Dhuhr = [-20, -5] }";
+        let mut daemon = paris_daemon(edits, &clock, notifier);
+        let directory =
+            std::env::temp_dir().join(format!("prayer-times-pause-daemon-{}", std::process::id()));
+        daemon.pause_file = directory.join("pause");
+
+        // Paused until 10 minutes before the adhan, the first reminder is only logged
+        let dhuhr_time = daemon.next_prayer.date_time().to_utc();
+        quiet::pause(&daemon.pause_file, dhuhr_time - Duration::minutes(10)).unwrap();
+        while clock.now.get() <= dhuhr_time {
+            daemon.step();
         }
+        std::fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(*sent.borrow(), ["Adhan Dhuhr in 5 minutes", "Dhuhr"]);
+    }
+
+    #[test]
+    fn test_next_prayer_signals_while_paused() {
+        let clock = fake_clock();
+        let notifier = RecordingNotifier::default();
+        let sent = Rc::clone(&notifier.sent);
+        let signals = Rc::clone(&notifier.signals);
+        let mut daemon = paris_daemon(NOTIFY_BEFORE, &clock, notifier);
+        let directory =
+            std::env::temp_dir().join(format!("prayer-times-signals-{}", std::process::id()));
+        daemon.pause_file = directory.join("pause");
+        quiet::pause(&daemon.pause_file, clock.now.get() + Duration::days(1)).unwrap();
+
+        // The adhan is not notified, the next prayer still changes
+        let dhuhr_time = daemon.next_prayer.date_time().to_utc();
+        while clock.now.get() <= dhuhr_time {
+            daemon.step();
+        }
+        assert!(sent.borrow().is_empty());
+        assert_eq!(*signals.borrow(), ["Dhuhr reached, next Asr"]);
+
+        // And when it is computed again in another timezone
+        clock.timezone.set(chrono_tz::Asia::Tokyo);
+        daemon.step();
+        std::fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(signals.borrow().len(), 2);
+        assert!(signals.borrow()[1].starts_with("next "));
+    }
+
+    #[test]
+    fn test_socket_requests() {
+        let clock = fake_clock();
+        let mut daemon = paris_daemon("", &clock, RecordingNotifier::default());
+        let directory =
+            std::env::temp_dir().join(format!("prayer-times-requests-{}", std::process::id()));
+        daemon.pause_file = directory.join("pause");
+
+        let today = daemon.handle_request(Request::Today).unwrap();
+        assert_eq!(today.values.len(), 8);
+        let next = daemon.handle_request(Request::Next).unwrap();
+        assert_eq!(next.values[0]["name"], "Dhuhr");
+        assert!(matches!(
+            daemon.handle_request(Request::Snooze),
+            Err(Error::NothingToSnooze)
+        ));
+
+        let pause = Request::Pause {
+            duration: "1h".to_string(),
+        };
+        let status = daemon.handle_request(pause).unwrap();
+        assert!(status.values[0]["paused_until"].is_string());
+        let status = daemon.handle_request(Request::Resume).unwrap();
+        assert!(status.values[0]["paused_until"].is_null());
+        std::fs::remove_dir_all(&directory).unwrap();
 
-        // Update next prayer
-        self.next_prayer = prayers::next(self.config);
-        self.is_notified_before = false;
+        let pause = Request::Pause {
+            duration: "soon".to_string(),
+        };
+        assert!(daemon.handle_request(pause).is_err());
     }
 
-    fn should_notify_before(&self) -> bool {
-        const MINUTES_BEFORE: i64 = 10;
-        self.config.notify_before()
-            && !self.is_notified_before
-            && self.next_prayer.time_remaining() < chrono::Duration::minutes(MINUTES_BEFORE + 1)
+    #[test]
+    fn test_requests_answer_like_the_commands() {
+        let clock = fake_clock();
+        let mut daemon = paris_daemon("", &clock, RecordingNotifier::default());
+        // 18:40 in Paris, before the sunset the daemon doesn't notify
+        let now = Utc.with_ymd_and_hms(2026, 3, 19, 17, 40, 0).unwrap();
+        clock.now.set(now);
+        let (parameters, command_clock) = (daemon.parameters.clone(), FixedClock(now));
+
+        let next = daemon.handle_request(Request::Next).unwrap();
+        let prayer = prayers::next(&parameters, &command_clock).unwrap();
+        assert_eq!(next.values[0]["event"], "Sunset");
+        assert_eq!(next.text, prayer.text_duration(&command_clock));
+        assert_eq!(
+            next.values[0],
+            output::to_value(&prayer, &parameters, &command_clock).unwrap()
+        );
+
+        let current = daemon.handle_request(Request::Current).unwrap();
+        let prayer = prayers::current(&parameters, &command_clock).unwrap();
+        assert_eq!(current.values[0]["event"], "Asr");
+        assert_eq!(current.text, prayer.text_time());
+    }
+
+    #[test]
+    fn test_socket_pause_out_of_range() {
+        let clock = fake_clock();
+        let mut daemon = paris_daemon("", &clock, RecordingNotifier::default());
+        let directory =
+            std::env::temp_dir().join(format!("prayer-times-overflow-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        daemon.pause_file = directory.join("pause");
+        let path = directory.join("daemon.sock");
+        let (sender, messages) = mpsc::channel();
+        ipc::serve(&path, sender).unwrap();
+        daemon.messages = messages;
+
+        let client = std::thread::spawn(move || {
+            let stream = UnixStream::connect(&path).unwrap();
+            let mut reader = BufReader::new(&stream);
+            let mut ask = |line: &str| -> Response {
+                writeln!(&stream, "{}", line).unwrap();
+                let mut answer = String::new();
+                reader.read_line(&mut answer).unwrap();
+                serde_json::from_str(&answer).unwrap()
+            };
+            let pause = ask(r#"{"command": "pause", "for": "99999999999h"}"#);
+            let status = ask(r#"{"command": "status"}"#);
+            (pause, status)
+        });
+        // The daemon answers both requests, it is still running after the first one
+        for _ in 0..2 {
+            let message = daemon.messages.recv().unwrap();
+            daemon.handle_message(message);
+        }
+        let (pause, status) = client.join().unwrap();
+        assert!(!pause.ok);
+        assert!(pause
+            .error
+            .unwrap()
+            .starts_with("Invalid duration '99999999999h'"));
+        assert!(status.ok);
+        assert!(status.values[0]["paused_until"].is_null());
+        std::fs::remove_dir_all(&directory).unwrap();
     }
 
-    fn notify_before_prayer(&mut self) {
-        notify_before_prayer(
-            &self.next_prayer,
-            self.next_prayer.time_remaining(),
-            self.config,
+    #[test]
+    fn test_starts_during_polar_night() {
+        let config = test_config(
+            r#"
+            location = { lat = 69.6492, lon = 18.9553 }
+            timezone = "Europe/Oslo"
+            "#,
         );
-        self.is_notified_before = true;
+        let clock = fake_clock();
+        let start = Utc.with_ymd_and_hms(2025, 12, 21, 8, 0, 0).unwrap();
+        clock.now.set(start);
+        let notifier = RecordingNotifier::default();
+        let sent = Rc::clone(&notifier.sent);
+
+        // The sun doesn't rise, the events that still happen are notified
+        let mut daemon = PrayerDaemon::new(
+            config,
+            Box::new(|| Ok(Config::default())),
+            &clock,
+            Box::new(notifier),
+            mpsc::channel().1,
+        );
+        assert!(clock.sleeps.borrow().is_empty());
+        assert_eq!(daemon.next_prayer.event(), Event::Dhuhr);
+        let end = start + Duration::days(1);
+        while clock.now.get() < end {
+            daemon.step();
+        }
+        assert_eq!(*sent.borrow(), ["Dhuhr", "Asr", "Isha", "Fajr"]);
     }
 
-    fn sleep(&self) {
-        println!("Next check in {} seconds", self.config.interval());
-        std::thread::sleep(std::time::Duration::from_secs(self.config.interval()));
+    #[test]
+    fn test_reload_config() {
+        let clock = fake_clock();
+        let mut daemon = paris_daemon("", &clock, RecordingNotifier::default());
+        // Edited config in Mecca, with the reminders before the prayers
+        let mecca = test_config(
+            r#"
+            location = { lat = 21.4225, lon = 39.8262 }
+            timezone = "Asia/Riyadh"
+            notification = { notify_before = true }
+            "#,
+        );
+        let edited: Rc<RefCell<Option<Config>>> = Rc::default();
+        let file = Rc::clone(&edited);
+        daemon.load_config = Box::new(move || {
+            file.borrow().clone().ok_or_else(|| {
+                prayer_times::Error::ConfigParse("invalid config".to_string()).into()
+            })
+        });
+
+        // An invalid edit keeps the current config
+        let dhuhr = daemon.next_prayer.clone();
+        assert!(daemon.handle_request(Request::Reload).is_err());
+        assert!(daemon.next_prayer == dhuhr);
+        assert!(daemon.reminders.is_empty());
+
+        // 13:00 in Mecca, after Dhuhr
+        *edited.borrow_mut() = Some(mecca);
+        daemon.handle_message(Message::Reload("config file changed"));
+        assert_eq!(daemon.next_prayer.event(), Event::Asr);
+        assert_eq!(
+            daemon.next_prayer.date_time().timezone(),
+            chrono_tz::Asia::Riyadh
+        );
+        assert_eq!(daemon.reminders.len(), 1);
+        assert!(daemon.reminders[0].prayer == daemon.next_prayer);
     }
 }
@@ -0,0 +1,291 @@
+use crate::app_error::Error;
+use crate::config::Config;
+use crate::daemon::Message;
+use crate::ipc::{ask_daemon, Request, Response};
+use crate::notification::Notifier;
+use chrono::Duration;
+use prayer_times::Prayer;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::mpsc::Sender;
+use zbus::blocking::connection;
+use zbus::fdo;
+use zbus::object_server::SignalEmitter;
+use zbus::zvariant;
+
+// Name of the daemon on the session bus, its object and its interface
+pub const BUS_NAME: &str = "io.github.Yasso9.PrayerTimes";
+const OBJECT_PATH: &str = "/io/github/Yasso9/PrayerTimes";
+const INTERFACE: &str = "io.github.Yasso9.PrayerTimes";
+
+// Methods and properties of the daemon, answered by the daemon like the requests of the socket
+struct PrayerTimesInterface {
+    messages: Sender<Message>,
+}
+
+impl PrayerTimesInterface {
+    // The daemon answers between two notifications, the wait happens on a thread of its own so
+    // that the executor of the bus keeps serving the other calls
+    async fn ask(&self, request: Request) -> fdo::Result<Response> {
+        let messages = self.messages.clone();
+        let response = blocking::unblock(move || ask_daemon(request, &messages)).await;
+        match response.error {
+            Some(error) => Err(fdo::Error::Failed(error)),
+            None => Ok(response),
+        }
+    }
+
+    // First value of the response, the next prayer or the status
+    async fn first_value(&self, request: Request) -> fdo::Result<Value> {
+        let response = self.ask(request).await?;
+        response
+            .values
+            .into_iter()
+            .next()
+            .ok_or_else(|| fdo::Error::Failed("Empty response of the daemon".to_string()))
+    }
+}
+
+fn field(value: &Value, key: &str) -> String {
+    value[key].as_str().unwrap_or_default().to_string()
+}
+
+#[zbus::interface(name = "io.github.Yasso9.PrayerTimes")]
+impl PrayerTimesInterface {
+    // Event, name, time in RFC 3339 format and seconds remaining of the next prayer
+    async fn get_next_prayer(&self) -> fdo::Result<(String, String, String, i64)> {
+        let prayer = self.first_value(Request::Next).await?;
+        Ok((
+            field(&prayer, "event"),
+            field(&prayer, "name"),
+            field(&prayer, "datetime"),
+            prayer["remaining_seconds"].as_i64().unwrap_or_default(),
+        ))
+    }
+
+    // Event, name and time in RFC 3339 format of the prayers of the day
+    async fn get_today_schedule(&self) -> fdo::Result<Vec<(String, String, String)>> {
+        let response = self.ask(Request::Today).await?;
+        Ok(response
+            .values
+            .iter()
+            .map(|prayer| {
+                (
+                    field(prayer, "event"),
+                    field(prayer, "name"),
+                    field(prayer, "datetime"),
+                )
+            })
+            .collect())
+    }
+
+    async fn snooze(&self) -> fdo::Result<()> {
+        self.ask(Request::Snooze).await.map(drop)
+    }
+
+    async fn reload(&self) -> fdo::Result<()> {
+        self.ask(Request::Reload).await.map(drop)
+    }
+
+    #[zbus(property)]
+    async fn next_event(&self) -> fdo::Result<String> {
+        Ok(field(
+            &self.first_value(Request::Status).await?,
+            "next_event",
+        ))
+    }
+
+    #[zbus(property)]
+    async fn next_time(&self) -> fdo::Result<String> {
+        Ok(field(
+            &self.first_value(Request::Status).await?,
+            "next_time",
+        ))
+    }
+
+    // Event, name and time in RFC 3339 format of the prayer whose time came
+    #[zbus(signal)]
+    async fn prayer_time_reached(
+        emitter: &SignalEmitter<'_>,
+        event: &str,
+        name: &str,
+        time: &str,
+    ) -> zbus::Result<()>;
+}
+
+// Expose the daemon on the session bus, or the bus at `address`. The notifier sends its signals
+pub fn serve(address: Option<&str>, messages: Sender<Message>) -> Result<DbusNotifier, Error> {
+    let dbus_error = |error: zbus::Error| Error::Dbus(error.to_string());
+    let builder = match address {
+        Some(address) => connection::Builder::address(address),
+        None => connection::Builder::session(),
+    };
+    let connection = builder
+        .and_then(|builder| builder.name(BUS_NAME))
+        .and_then(|builder| builder.serve_at(OBJECT_PATH, PrayerTimesInterface { messages }))
+        .and_then(|builder| builder.build())
+        .map_err(dbus_error)?;
+    println!("Serving {} on the session bus", BUS_NAME);
+    Ok(DbusNotifier { connection })
+}
+
+// Signals of the daemon on the bus
+pub struct DbusNotifier {
+    connection: zbus::blocking::Connection,
+}
+
+impl DbusNotifier {
+    fn emit<B>(&self, interface: &str, signal: &str, body: &B) -> Result<(), Error>
+    where
+        B: serde::Serialize + zvariant::DynamicType,
+    {
+        self.connection
+            .emit_signal(None::<&str>, OBJECT_PATH, interface, signal, body)
+            .map_err(|error| Error::Dbus(error.to_string()))
+    }
+}
+
+// The signals follow the next prayer of the daemon, not the notifications that can be quiet
+impl Notifier for DbusNotifier {
+    fn prayer(&self, _prayer: &Prayer, _config: &Config) -> Result<(), Error> {
+        Ok(())
+    }
+    fn reminder(&self, _prayer: &Prayer, _offset: Duration, _config: &Config) -> Result<(), Error> {
+        Ok(())
+    }
+    fn missed_prayer(&self, _prayer: &Prayer, _config: &Config) -> Result<(), Error> {
+        Ok(())
+    }
+    fn iqama(&self, _prayer: &Prayer, _config: &Config) -> Result<(), Error> {
+        Ok(())
+    }
+    fn departure(&self, _prayer: &Prayer, _config: &Config) -> Result<(), Error> {
+        Ok(())
+    }
+    fn next_prayer(&self, next: &Prayer, reached: Option<&Prayer>) -> Result<(), Error> {
+        if let Some(prayer) = reached {
+            let time = prayer.date_time().to_rfc3339();
+            self.emit(
+                INTERFACE,
+                "PrayerTimeReached",
+                &(prayer.event().to_string(), prayer.name(), time),
+            )?;
+        }
+        let event = next.event().to_string();
+        let time = next.date_time().to_rfc3339();
+        let changed = HashMap::from([
+            ("NextEvent", zvariant::Value::from(event.as_str())),
+            ("NextTime", zvariant::Value::from(time.as_str())),
+        ]);
+        self.emit(
+            "org.freedesktop.DBus.Properties",
+            "PropertiesChanged",
+            &(INTERFACE, changed, Vec::<&str>::new()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prayer_times::{Event, Location, Parameters};
+    use std::io::{BufRead, BufReader};
+    use std::process::{Command, Stdio};
+    use std::sync::mpsc;
+    use std::thread;
+    use zbus::blocking::Proxy;
+
+    #[test]
+    #[ignore = "needs the dbus-daemon program, run with --ignored"]
+    fn test_private_bus() {
+        // Private bus, the test needs the `dbus-daemon` program
+        let mut bus = Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("dbus-daemon is needed for this test");
+        let mut address = String::new();
+        let stdout = bus.stdout.take().unwrap();
+        BufReader::new(stdout).read_line(&mut address).unwrap();
+        let address = address.trim();
+
+        // Daemon answering the requests
+        let (sender, messages) = mpsc::channel();
+        thread::spawn(move || {
+            while let Ok(Message::Request(request, answer)) = messages.recv() {
+                let prayer = serde_json::json!({
+                    "event": "Asr",
+                    "name": "Asr",
+                    "datetime": "2026-03-20T16:18:03+01:00",
+                    "remaining_seconds": 600,
+                    "next_event": "Asr",
+                });
+                let response = match request {
+                    Request::Snooze => Err(Error::NothingToSnooze),
+                    // Answered later, like a daemon busy with a notification
+                    Request::Reload => {
+                        thread::spawn(move || {
+                            thread::sleep(std::time::Duration::from_secs(2));
+                            let _ = answer.send(Response::default());
+                        });
+                        continue;
+                    }
+                    _ => Ok(Response::new(String::new(), vec![prayer])),
+                };
+                let _ = answer.send(Response::from(response));
+            }
+        });
+        let notifier = serve(Some(address), sender).unwrap();
+
+        let client = connection::Builder::address(address)
+            .unwrap()
+            .build()
+            .unwrap();
+        let proxy = Proxy::new(&client, BUS_NAME, OBJECT_PATH, INTERFACE).unwrap();
+        let next: (String, String, String, i64) = proxy.call("GetNextPrayer", &()).unwrap();
+        assert_eq!(next.0, "Asr");
+        assert_eq!(next.3, 600);
+        let today: Vec<(String, String, String)> = proxy.call("GetTodaySchedule", &()).unwrap();
+        assert_eq!(today.len(), 1);
+        let event: String = proxy.get_property("NextEvent").unwrap();
+        assert_eq!(event, "Asr");
+        assert!(proxy.call::<_, _, ()>("Snooze", &()).is_err());
+
+        // A slow answer doesn't hold the other calls
+        let reload_client = client.clone();
+        let reload = thread::spawn(move || {
+            let proxy = Proxy::new(&reload_client, BUS_NAME, OBJECT_PATH, INTERFACE).unwrap();
+            proxy.call::<_, _, ()>("Reload", &())
+        });
+        thread::sleep(std::time::Duration::from_millis(200));
+        let start = std::time::Instant::now();
+        let next: (String, String, String, i64) = proxy.call("GetNextPrayer", &()).unwrap();
+        assert_eq!(next.0, "Asr");
+        assert!(start.elapsed() < std::time::Duration::from_secs(1));
+        assert!(reload.join().unwrap().is_ok());
+
+        let mut signals = proxy.receive_signal("PrayerTimeReached").unwrap();
+        let parameters = Parameters::new(
+            Location {
+                lat: 48.8566,
+                lon: 2.3522,
+            },
+            chrono_tz::Europe::Paris,
+        );
+        let date = chrono::NaiveDate::from_ymd_opt(2026, 3, 20).unwrap();
+        let asr = Prayer::new(Event::Asr, date, &parameters).unwrap();
+        notifier
+            .next_prayer(&asr.next().unwrap(), Some(&asr))
+            .unwrap();
+        let signal = signals.next().unwrap();
+        let (event, _, time): (String, String, String) = signal.body().deserialize().unwrap();
+        assert_eq!(
+            (event.as_str(), time),
+            ("Asr", asr.date_time().to_rfc3339())
+        );
+
+        bus.kill().unwrap();
+        bus.wait().unwrap();
+    }
+}
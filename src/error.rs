@@ -0,0 +1,34 @@
+use crate::event::Event;
+use chrono::NaiveDate;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum Error {
+    /// No location given and impossible to get it automatically
+    LocationUnavailable,
+    /// The event does not happen on this date at this location (polar day or night)
+    UndefinedCalculation { event: Event, date: NaiveDate },
+    /// The config file could not be read
+    ConfigParse(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::LocationUnavailable => write!(
+                f,
+                "No location provided in arguments or config file and impossible to get it automatically. \
+                Run the program using the latitude and longitude arguments or set them in the config file. \
+                Example: prayer-times --latitude <LAT> --longitude <LON>"
+            ),
+            Error::UndefinedCalculation { event, date } => {
+                write!(f, "{} cannot be calculated on the {} at this location", event, date)
+            }
+            Error::ConfigParse(reason) => write!(f, "Error reading config file: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -1,6 +1,7 @@
+use serde::{Deserialize, Serialize};
 use strum_macros::Display;
 
-#[derive(Clone, Copy, Display, PartialEq)]
+#[derive(Debug, Clone, Copy, Display, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Event {
     Fajr,
     Sunrise,
@@ -0,0 +1,45 @@
+use serde::Deserialize;
+use serde::Serialize;
+use strum::IntoEnumIterator;
+use strum_macros::Display;
+use strum_macros::EnumIter;
+use strum_macros::EnumString;
+
+/// Latitude used by the nearest latitude rule
+pub const NEAREST_LATITUDE: f64 = 48.;
+
+// https://praytimes.org/calculation#higher_latitudes
+#[derive(
+    Default, Debug, Clone, Copy, PartialEq, EnumString, Serialize, Deserialize, EnumIter, Display,
+)]
+pub enum HighLatitudeRule {
+    /// Fajr and Isha are at most half of the night away from sunrise and sunset
+    MiddleOfTheNight,
+    /// Fajr and Isha are at most a seventh of the night away from sunrise and sunset
+    OneSeventh,
+    /// Fajr and Isha are at most angle/60 of the night away from sunrise and sunset
+    #[default]
+    AngleBased,
+    /// Fajr and Isha are calculated as if the location was at the nearest latitude
+    /// where twilight still exists
+    NearestLatitude,
+}
+impl HighLatitudeRule {
+    /// Part of the night between sunset and sunrise that can separate Fajr from sunrise
+    /// and Isha from sunset
+    pub fn night_portion(&self, angle: f64) -> f64 {
+        match self {
+            HighLatitudeRule::MiddleOfTheNight => 1. / 2.,
+            HighLatitudeRule::OneSeventh => 1. / 7.,
+            // The nearest latitude rule can still be undefined for big angles,
+            // so we fallback to the angle based rule
+            HighLatitudeRule::AngleBased | HighLatitudeRule::NearestLatitude => angle / 60.,
+        }
+    }
+
+    pub fn list_all() {
+        for variant in Self::iter() {
+            println!("{}", variant);
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use crate::event::Event;
+use crate::parameters::Parameters;
+use crate::prayer::Prayer;
+use chrono::{DateTime, Datelike, Days, NaiveDate};
+use chrono_tz::Tz;
+use serde::Serialize;
+use std::fmt;
+
+/// Maximum number of days of the adjustment for the local moon sighting
+pub const MAX_ADJUSTMENT: i8 = 2;
+
+const MONTH_NAMES: [&str; 12] = [
+    "Muharram",
+    "Safar",
+    "Rabi al-Awwal",
+    "Rabi al-Thani",
+    "Jumada al-Awwal",
+    "Jumada al-Thani",
+    "Rajab",
+    "Shaban",
+    "Ramadan",
+    "Shawwal",
+    "Dhu al-Qadah",
+    "Dhu al-Hijjah",
+];
+
+// Julian day number of the 1st Muharram of the year 1 (civil epoch, 16 July 622 in the julian calendar)
+const EPOCH_JULIAN_DAY: i64 = 1948440;
+// Difference between a julian day number and the days since the common era of chrono
+const JULIAN_DAY_CE_OFFSET: i64 = 1721425;
+
+/// Date in the tabular (arithmetical) Islamic calendar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct HijriDate {
+    pub year: i32,
+    /// Month between 1 (Muharram) and 12 (Dhu al-Hijjah)
+    pub month: u32,
+    pub day: u32,
+}
+
+impl HijriDate {
+    /// Hijri date of a gregorian day, moved by `adjustment` days for the local moon sighting
+    pub fn from_gregorian(date: NaiveDate, adjustment: i8) -> Self {
+        let adjustment = adjustment.clamp(-MAX_ADJUSTMENT, MAX_ADJUSTMENT) as i64;
+        let julian_day = date.num_days_from_ce() as i64 + JULIAN_DAY_CE_OFFSET + adjustment;
+        Self::from_julian_day(julian_day)
+    }
+
+    /// Hijri date at a moment, the day starts at Maghrib instead of midnight
+    pub fn at(date_time: DateTime<Tz>, parameters: &Parameters) -> Self {
+        let date = date_time.date_naive();
+        // Without Maghrib (polar day or night), the day starts at midnight
+        let after_maghrib = Prayer::new(Event::Maghrib, date, parameters)
+            .is_ok_and(|maghrib| date_time >= maghrib.date_time());
+        let day = if after_maghrib {
+            date.checked_add_days(Days::new(1)).unwrap_or(date)
+        } else {
+            date
+        };
+        Self::from_gregorian(day, parameters.hijri_adjustment)
+    }
+
+    /// Gregorian day of the Hijri date, `None` if the date doesn't exist
+    pub fn to_gregorian(&self, adjustment: i8) -> Option<NaiveDate> {
+        if !(1..=12).contains(&self.month) || self.day < 1 || self.day > self.month_length() {
+            return None;
+        }
+        let adjustment = adjustment.clamp(-MAX_ADJUSTMENT, MAX_ADJUSTMENT) as i64;
+        let days_from_ce = self.julian_day() - JULIAN_DAY_CE_OFFSET - adjustment;
+        NaiveDate::from_num_days_from_ce_opt(i32::try_from(days_from_ce).ok()?)
+    }
+
+    pub fn month_name(&self) -> &'static str {
+        MONTH_NAMES[(self.month.clamp(1, 12) - 1) as usize]
+    }
+
+    /// 11 years of a 30 years cycle have 355 days instead of 354
+    pub fn is_leap_year(&self) -> bool {
+        (14 + 11 * self.year).rem_euclid(30) < 11
+    }
+
+    /// Odd months have 30 days and even months 29, except Dhu al-Hijjah of leap years
+    pub fn month_length(&self) -> u32 {
+        if self.month % 2 == 1 || (self.month == 12 && self.is_leap_year()) {
+            30
+        } else {
+            29
+        }
+    }
+
+    // https://en.wikipedia.org/wiki/Tabular_Islamic_calendar
+    fn julian_day(&self) -> i64 {
+        let year = self.year as i64;
+        let month = self.month as i64;
+        self.day as i64
+            + (59 * (month - 1) + 1) / 2
+            + (year - 1) * 354
+            + (3 + 11 * year).div_euclid(30)
+            + EPOCH_JULIAN_DAY
+            - 1
+    }
+
+    fn from_julian_day(julian_day: i64) -> Self {
+        let year = (30 * (julian_day - EPOCH_JULIAN_DAY) + 10646).div_euclid(10631) as i32;
+        let first_day_of_year = HijriDate {
+            year,
+            month: 1,
+            day: 1,
+        }
+        .julian_day();
+        // Months alternate between 30 and 29 days, so 59 days every 2 months
+        let month = ((2 * (julian_day - first_day_of_year)).div_euclid(59) + 1).clamp(1, 12) as u32;
+        let first_day_of_month = HijriDate {
+            year,
+            month,
+            day: 1,
+        }
+        .julian_day();
+        Self {
+            year,
+            month,
+            day: (julian_day - first_day_of_month + 1) as u32,
+        }
+    }
+}
+
+impl fmt::Display for HijriDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.day, self.month_name(), self.year)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::location::Location;
+
+    #[test]
+    fn test_gregorian_to_hijri() {
+        let cases = [
+            ((622, 7, 19), (1, 1, 1)),
+            ((2000, 1, 1), (1420, 9, 24)),
+            ((2023, 7, 19), (1445, 1, 1)),
+            ((2024, 3, 11), (1445, 9, 1)),
+            ((2025, 6, 27), (1447, 1, 1)),
+            ((2026, 2, 18), (1447, 9, 1)),
+        ];
+        for ((year, month, day), (hijri_year, hijri_month, hijri_day)) in cases {
+            let date = NaiveDate::from_ymd_opt(year, month, day).unwrap();
+            let hijri = HijriDate::from_gregorian(date, 0);
+            assert_eq!(
+                (hijri.year, hijri.month, hijri.day),
+                (hijri_year, hijri_month, hijri_day)
+            );
+            assert_eq!(hijri.to_gregorian(0), Some(date));
+        }
+    }
+
+    #[test]
+    fn test_round_trip_and_adjustment() {
+        let start = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap();
+        for date in start.iter_days().take(3000) {
+            let hijri = HijriDate::from_gregorian(date, 1);
+            assert!(hijri.day >= 1 && hijri.day <= hijri.month_length());
+            assert_eq!(hijri.to_gregorian(1), Some(date));
+        }
+
+        // Moon sighted one day later than the tabular calendar
+        let hijri = HijriDate::from_gregorian(NaiveDate::from_ymd_opt(2026, 2, 18).unwrap(), -1);
+        assert_eq!(hijri.to_string(), "29 Shaban 1447");
+        let invalid = HijriDate {
+            year: 1447,
+            month: 8,
+            day: 30,
+        };
+        assert_eq!(invalid.to_gregorian(0), None);
+    }
+
+    #[test]
+    fn test_day_starts_at_maghrib() {
+        let parameters = Parameters::new(
+            Location {
+                lat: 21.42664,
+                lon: 39.82563,
+            },
+            chrono_tz::Asia::Riyadh,
+        );
+        let date = NaiveDate::from_ymd_opt(2026, 2, 17).unwrap();
+        let maghrib = Prayer::new(Event::Maghrib, date, &parameters).unwrap();
+        let before = maghrib.date_time() - chrono::Duration::minutes(1);
+
+        assert_eq!(
+            HijriDate::at(before, &parameters).to_string(),
+            "29 Shaban 1447"
+        );
+        assert_eq!(
+            HijriDate::at(maghrib.date_time(), &parameters).to_string(),
+            "1 Ramadan 1447"
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use crate::app_error::Error;
+use crate::config::Config;
+use crate::notification::{NotificationKind, Notifier};
+use chrono::{DateTime, Duration};
+use chrono_tz::Tz;
+use prayer_times::{Clock, HijriDate, Prayer};
+use std::process::{Command, ExitStatus, Stdio};
+use std::thread::{self, JoinHandle};
+
+// Time between two checks of a running hook
+const POLL_MILLISECONDS: u64 = 100;
+
+// Runs the commands of the `[hooks]` config, they don't need a desktop session
+pub struct HookNotifier<'a> {
+    pub clock: &'a dyn Clock,
+}
+
+impl HookNotifier<'_> {
+    // `moment` is the time the hook is about, the adhan, the iqama or the khutbah
+    fn run(
+        &self,
+        kind: NotificationKind,
+        prayer: &Prayer,
+        moment: DateTime<Tz>,
+        config: &Config,
+    ) -> Result<(), Error> {
+        let commands = config.hooks(kind, prayer.event());
+        if commands.is_empty() {
+            return Ok(());
+        }
+        let hijri = HijriDate::at(prayer.date_time(), &config.parameters());
+        // Rounded to the closest minute, the hooks run a few milliseconds after their time
+        let seconds = (moment.to_utc() - self.clock.now()).num_seconds();
+        let minutes_remaining = (seconds as f64 / 60.).round() as i64;
+        let env = [
+            ("HOOK_TYPE", kind.to_string()),
+            ("PRAYER_EVENT", prayer.event().to_string()),
+            ("PRAYER_NAME", prayer.name().to_string()),
+            ("PRAYER_TIME", prayer.date_time().to_rfc3339()),
+            ("MINUTES_REMAINING", minutes_remaining.to_string()),
+            ("HIJRI_DATE", hijri.to_string()),
+        ];
+        // A hook that cannot start doesn't prevent the others from running
+        let mut result = Ok(());
+        for command in commands {
+            if let Err(error) = run_command(command, &env, config.hook_timeout()) {
+                eprintln!("{}", error);
+                result = Err(error);
+            }
+        }
+        result
+    }
+}
+
+impl Notifier for HookNotifier<'_> {
+    fn prayer(&self, prayer: &Prayer, config: &Config) -> Result<(), Error> {
+        self.run(NotificationKind::Adhan, prayer, prayer.date_time(), config)
+    }
+    fn reminder(&self, prayer: &Prayer, _offset: Duration, config: &Config) -> Result<(), Error> {
+        self.run(
+            NotificationKind::Reminder,
+            prayer,
+            prayer.date_time(),
+            config,
+        )
+    }
+    fn missed_prayer(&self, prayer: &Prayer, config: &Config) -> Result<(), Error> {
+        self.run(NotificationKind::Missed, prayer, prayer.date_time(), config)
+    }
+    fn iqama(&self, prayer: &Prayer, config: &Config) -> Result<(), Error> {
+        let iqama = prayer.iqama().unwrap_or(prayer.date_time());
+        self.run(NotificationKind::Iqama, prayer, iqama, config)
+    }
+    fn departure(&self, prayer: &Prayer, config: &Config) -> Result<(), Error> {
+        let khutbah = prayer.khutbah().unwrap_or(prayer.date_time());
+        self.run(NotificationKind::Departure, prayer, khutbah, config)
+    }
+    fn next_prayer(&self, _next: &Prayer, _reached: Option<&Prayer>) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+// Start a shell command without waiting for it, it is killed after `timeout`.
+// The handle gives its exit status, `None` when it was killed
+fn run_command(
+    command: &str,
+    env: &[(&str, String)],
+    timeout: Duration,
+) -> Result<JoinHandle<Option<ExitStatus>>, Error> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .envs(env.iter().map(|(key, value)| (key, value)))
+        .stdin(Stdio::null())
+        .spawn()
+        .map_err(|error| Error::Hook(format!("{}: {}", command, error)))?;
+    println!("Started hook: {}", command);
+
+    let command = command.to_string();
+    let deadline = std::time::Instant::now() + timeout.to_std().unwrap_or_default();
+    Ok(thread::spawn(move || loop {
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                if status.success() {
+                    println!("Hook finished: {}", command);
+                } else {
+                    eprintln!("Hook failed with {}: {}", status, command);
+                }
+                return Some(status);
+            }
+            Ok(None) if std::time::Instant::now() >= deadline => {
+                eprintln!("Hook timed out, killing it: {}", command);
+                // Also reaps the process so it doesn't stay a zombie
+                if let Err(error) = child.kill().and_then(|_| child.wait()) {
+                    eprintln!("Failed to kill the hook {}: {}", command, error);
+                }
+                return None;
+            }
+            Ok(None) => thread::sleep(std::time::Duration::from_millis(POLL_MILLISECONDS)),
+            Err(error) => {
+                eprintln!("Failed to wait for the hook {}: {}", command, error);
+                return None;
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hook_environment_and_status() {
+        let env = [("PRAYER_EVENT", "Asr".to_string())];
+        let handle = run_command("test \"$PRAYER_EVENT\" = Asr", &env, Duration::seconds(5));
+        let status = handle.unwrap().join().unwrap();
+        assert!(status.is_some_and(|status| status.success()));
+
+        let handle = run_command("exit 3", &env, Duration::seconds(5));
+        let status = handle.unwrap().join().unwrap();
+        assert_eq!(status.and_then(|status| status.code()), Some(3));
+    }
+
+    #[test]
+    fn test_hook_timeout() {
+        let start = std::time::Instant::now();
+        let handle = run_command("sleep 10", &[], Duration::milliseconds(200)).unwrap();
+        assert_eq!(handle.join().unwrap(), None);
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    }
+}
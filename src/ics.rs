@@ -0,0 +1,284 @@
+use crate::app_error::Error;
+use crate::notification::reminder_summary;
+use chrono::{Days, Duration, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Utc};
+use chrono_tz::{OffsetComponents, OffsetName, Tz};
+use prayer_times::{prayers, Event, Parameters, Prayer};
+use std::collections::BTreeMap;
+
+const PROGRAM_NAME: &str = env!("CARGO_PKG_NAME");
+const LOCAL_DATE_TIME: &str = "%Y%m%dT%H%M%S";
+// Lines longer than this are folded
+const MAX_LINE_OCTETS: usize = 75;
+
+// iCalendar export of the prayers between two dates (included)
+// https://datatracker.ietf.org/doc/html/rfc5545
+pub fn calendar(
+    parameters: &Parameters,
+    from: NaiveDate,
+    to: NaiveDate,
+    reminders: &BTreeMap<Event, Vec<i64>>,
+) -> Result<String, Error> {
+    if to < from {
+        return Err(Error::InvalidRange { from, to });
+    }
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!(
+            "PRODID:-//{}//{} {}//EN",
+            PROGRAM_NAME,
+            PROGRAM_NAME,
+            env!("CARGO_PKG_VERSION")
+        ),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        "X-WR-CALNAME:Prayer Times".to_string(),
+        format!("X-WR-TIMEZONE:{}", parameters.timezone.name()),
+    ];
+    lines.extend(vtimezone(parameters.timezone, from, to));
+
+    let timestamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    for date in from.iter_days().take_while(|date| *date <= to) {
+        // An event that doesn't happen on the date (polar day or night) has no entry
+        for prayer in prayers::list_prayers_for_date(parameters, date)
+            .into_iter()
+            .flatten()
+        {
+            let alarms = reminders
+                .get(&prayer.event())
+                .map_or(&[][..], Vec::as_slice);
+            lines.extend(vevent(&prayer, date, parameters, &timestamp, alarms));
+        }
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    Ok(lines.iter().map(|line| fold(line)).collect())
+}
+
+fn vevent(
+    prayer: &Prayer,
+    date: NaiveDate,
+    parameters: &Parameters,
+    timestamp: &str,
+    alarms: &[i64],
+) -> Vec<String> {
+    let event = prayer.event();
+    // The UID only depends on the prayer and the location, so importing
+    // the calendar again updates the events instead of duplicating them
+    let uid = format!(
+        "{}-{}-{:.4}-{:.4}@{}",
+        date.format("%Y%m%d"),
+        event.to_string().to_lowercase(),
+        parameters.lat(),
+        parameters.lon(),
+        PROGRAM_NAME
+    );
+
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", uid),
+        format!("DTSTAMP:{}", timestamp),
+        format!(
+            "DTSTART;TZID={}:{}",
+            parameters.timezone.name(),
+            prayer.date_time().format(LOCAL_DATE_TIME)
+        ),
+        format!("SUMMARY:{}", escape_text(prayer.name())),
+        "TRANSP:TRANSPARENT".to_string(),
+    ];
+    for &minutes in alarms {
+        let sign = if minutes < 0 { "-" } else { "" };
+        lines.extend([
+            "BEGIN:VALARM".to_string(),
+            "ACTION:DISPLAY".to_string(),
+            format!(
+                "DESCRIPTION:{}",
+                escape_text(&reminder_summary(parameters.locale, prayer.name(), minutes))
+            ),
+            format!("TRIGGER:{}PT{}M", sign, minutes.abs()),
+            "END:VALARM".to_string(),
+        ]);
+    }
+    lines.push("END:VEVENT".to_string());
+    lines
+}
+
+// Timezone definition with every offset change happening in the calendar
+fn vtimezone(timezone: Tz, from: NaiveDate, to: NaiveDate) -> Vec<String> {
+    let start = from
+        .checked_sub_days(Days::new(1))
+        .unwrap_or(from)
+        .and_time(NaiveTime::MIN);
+    let end = to
+        .checked_add_days(Days::new(2))
+        .unwrap_or(to)
+        .and_time(NaiveTime::MIN);
+
+    let mut lines = vec![
+        "BEGIN:VTIMEZONE".to_string(),
+        format!("TZID:{}", timezone.name()),
+    ];
+
+    let mut offset = timezone.offset_from_utc_datetime(&start);
+    lines.extend(observance(&offset, &offset, start + offset.fix()));
+
+    // Offset changes are searched every 15 minutes, the smallest step used by timezones today,
+    // then to the second since some historical changes don't happen on a quarter hour
+    let changed = |offset: &<Tz as TimeZone>::Offset, instant: &NaiveDateTime| {
+        let next_offset = timezone.offset_from_utc_datetime(instant);
+        next_offset.fix() != offset.fix() || next_offset.abbreviation() != offset.abbreviation()
+    };
+    let mut instant = start;
+    while instant < end {
+        let next_instant = instant + Duration::minutes(15);
+        if changed(&offset, &next_instant) {
+            let (mut before, mut after) = (instant, next_instant);
+            while after - before > Duration::seconds(1) {
+                let middle = before + (after - before) / 2;
+                if changed(&offset, &middle) {
+                    after = middle;
+                } else {
+                    before = middle;
+                }
+            }
+            let next_offset = timezone.offset_from_utc_datetime(&after);
+            // The start of an observance is expressed in the offset before the change
+            lines.extend(observance(&offset, &next_offset, after + offset.fix()));
+            offset = next_offset;
+        }
+        instant = next_instant;
+    }
+
+    lines.push("END:VTIMEZONE".to_string());
+    lines
+}
+
+fn observance(
+    from: &<Tz as TimeZone>::Offset,
+    to: &<Tz as TimeZone>::Offset,
+    start: NaiveDateTime,
+) -> Vec<String> {
+    let kind = if to.dst_offset().is_zero() {
+        "STANDARD"
+    } else {
+        "DAYLIGHT"
+    };
+    let mut lines = vec![
+        format!("BEGIN:{}", kind),
+        format!("DTSTART:{}", start.format(LOCAL_DATE_TIME)),
+        format!(
+            "TZOFFSETFROM:{}",
+            format_offset(from.fix().local_minus_utc())
+        ),
+        format!("TZOFFSETTO:{}", format_offset(to.fix().local_minus_utc())),
+    ];
+    if let Some(abbreviation) = to.abbreviation() {
+        lines.push(format!("TZNAME:{}", escape_text(abbreviation)));
+    }
+    lines.push(format!("END:{}", kind));
+    lines
+}
+
+// The seconds are only written when there are some, for historical offsets
+fn format_offset(seconds: i32) -> String {
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let (minutes, seconds) = (seconds.abs() / 60, seconds.abs() % 60);
+    let offset = format!("{}{:02}{:02}", sign, minutes / 60, minutes % 60);
+    if seconds == 0 {
+        offset
+    } else {
+        format!("{}{:02}", offset, seconds)
+    }
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+// Split a content line in lines of 75 octets at most, ended by CRLF
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut line_length = 0;
+    for character in line.chars() {
+        if line_length + character.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            line_length = 1;
+        }
+        folded.push(character);
+        line_length += character.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fold_long_lines() {
+        let line = "DESCRIPTION:".to_string() + &"a".repeat(100);
+        let folded = fold(&line);
+        let lines: Vec<&str> = folded.split("\r\n").collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].len(), MAX_LINE_OCTETS);
+        assert!(lines[1].starts_with(' '));
+        assert_eq!(lines[2], "");
+    }
+
+    #[test]
+    fn test_vtimezone_paris_dst_change_march_2026() {
+        let from = NaiveDate::from_ymd_opt(2026, 3, 28).unwrap();
+        let to = NaiveDate::from_ymd_opt(2026, 3, 29).unwrap();
+        let lines = vtimezone(chrono_tz::Europe::Paris, from, to);
+
+        // Clocks go forward at 02:00 local time
+        let daylight = lines
+            .iter()
+            .position(|line| line == "BEGIN:DAYLIGHT")
+            .unwrap();
+        assert_eq!(lines[daylight + 1], "DTSTART:20260329T020000");
+        assert_eq!(lines[daylight + 2], "TZOFFSETFROM:+0100");
+        assert_eq!(lines[daylight + 3], "TZOFFSETTO:+0200");
+    }
+
+    #[test]
+    fn test_vtimezone_change_off_the_quarter_hour() {
+        // Liberia moved from UTC-00:44:30 to UTC at midnight, 00:44:30 UTC
+        let from = NaiveDate::from_ymd_opt(1972, 1, 6).unwrap();
+        let to = NaiveDate::from_ymd_opt(1972, 1, 7).unwrap();
+        let lines = vtimezone(chrono_tz::Africa::Monrovia, from, to);
+
+        let standard = lines
+            .iter()
+            .rposition(|line| line == "BEGIN:STANDARD")
+            .unwrap();
+        assert_eq!(lines[standard + 1], "DTSTART:19720107T000000");
+        assert_eq!(lines[standard + 2], "TZOFFSETFROM:-004430");
+        assert_eq!(lines[standard + 3], "TZOFFSETTO:+0000");
+    }
+
+    #[test]
+    fn test_invalid_ranges() {
+        let parameters = Parameters::new(
+            prayer_times::Location {
+                lat: 48.8566,
+                lon: 2.3522,
+            },
+            chrono_tz::Europe::Paris,
+        );
+        let from = NaiveDate::from_ymd_opt(2026, 3, 20).unwrap();
+        let to = NaiveDate::from_ymd_opt(2026, 3, 19).unwrap();
+        assert!(matches!(
+            calendar(&parameters, from, to, &BTreeMap::new()),
+            Err(Error::InvalidRange { .. })
+        ));
+        // No overflow at the first date
+        let lines = vtimezone(chrono_tz::Europe::Paris, NaiveDate::MIN, NaiveDate::MIN);
+        assert_eq!(lines.last().unwrap(), "END:VTIMEZONE");
+    }
+}
@@ -0,0 +1,232 @@
+use crate::app_error::Error;
+use crate::daemon::Message;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::Duration;
+
+// Longest wait for the answer of the daemon, it answers between two notifications
+const ANSWER_TIMEOUT_SECONDS: u64 = 5;
+
+// Request sent on the socket, one json object per line like `{"command": "next"}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "lowercase")]
+pub enum Request {
+    Next,
+    Current,
+    Today,
+    Status,
+    // Send the notification of the last notified prayer again in a few minutes
+    Snooze,
+    // Length of the pause, like 2h or 45m
+    Pause {
+        #[serde(rename = "for")]
+        duration: String,
+    },
+    Resume,
+    Reload,
+}
+
+// Answer to a request, on one line. `text` is the plain output of the command and `values` what
+// the other formats show
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Response {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub values: Vec<Value>,
+}
+
+impl Response {
+    pub fn new(text: String, values: Vec<Value>) -> Self {
+        Self {
+            ok: true,
+            error: None,
+            text,
+            values,
+        }
+    }
+}
+
+impl From<Result<Response, Error>> for Response {
+    fn from(result: Result<Response, Error>) -> Self {
+        result.unwrap_or_else(|error| Self {
+            error: Some(error.to_string()),
+            ..Default::default()
+        })
+    }
+}
+
+// Socket of the daemon in the runtime directory, there is none without it
+pub fn socket_path() -> Option<PathBuf> {
+    let runtime = std::env::var_os("XDG_RUNTIME_DIR").filter(|runtime| !runtime.is_empty())?;
+    Some(PathBuf::from(runtime).join(format!("{}.sock", env!("CARGO_PKG_NAME"))))
+}
+
+// Answer the requests of the socket in the background, they are passed to the daemon
+pub fn serve(path: &Path, messages: Sender<Message>) -> Result<(), Error> {
+    let listener = bind(path)?;
+    println!("Listening on {}", path.display());
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let messages = messages.clone();
+                    thread::spawn(move || {
+                        if let Err(error) = answer(stream, &messages) {
+                            eprintln!("Failed to answer on the socket: {}", error);
+                        }
+                    });
+                }
+                Err(error) => eprintln!("Failed to accept a connection: {}", error),
+            }
+        }
+    });
+    Ok(())
+}
+
+fn bind(path: &Path) -> Result<UnixListener, Error> {
+    let io_error = |error: std::io::Error| Error::Io(format!("{}: {}", path.display(), error));
+    if path.exists() {
+        // Left by a daemon that didn't stop cleanly, unless it is still running
+        if UnixStream::connect(path).is_ok() {
+            return Err(Error::Io(format!(
+                "Another daemon is listening on {}",
+                path.display()
+            )));
+        }
+        std::fs::remove_file(path).map_err(io_error)?;
+    }
+    UnixListener::bind(path).map_err(io_error)
+}
+
+fn answer(stream: UnixStream, messages: &Sender<Message>) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(ANSWER_TIMEOUT_SECONDS)))?;
+    let mut reader = BufReader::new(&stream);
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 0 {
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => ask_daemon(request, messages),
+            Err(error) => Response::from(Err(Error::InvalidRequest(error.to_string()))),
+        };
+        let mut writer = &stream;
+        writeln!(writer, "{}", json(&response))?;
+        line.clear();
+    }
+    Ok(())
+}
+
+// Pass a request to the daemon and wait for its response
+pub fn ask_daemon(request: Request, messages: &Sender<Message>) -> Response {
+    let (sender, receiver) = mpsc::channel();
+    if messages.send(Message::Request(request, sender)).is_err() {
+        return Response::from(Err(Error::Io("The daemon stopped".to_string())));
+    }
+    receiver
+        .recv_timeout(Duration::from_secs(ANSWER_TIMEOUT_SECONDS))
+        .unwrap_or_else(|_| Response::from(Err(Error::Io("The daemon didn't answer".to_string()))))
+}
+
+fn json(response: &Response) -> String {
+    serde_json::to_string(response).unwrap_or_else(|error| {
+        format!(
+            r#"{{"ok":false,"error":"{}"}}"#,
+            error.to_string().escape_default()
+        )
+    })
+}
+
+// Ask the running daemon, `None` when there is no daemon to ask
+pub fn request(request: &Request) -> Option<Result<Response, Error>> {
+    request_at(&socket_path()?, request)
+}
+
+fn request_at(path: &Path, request: &Request) -> Option<Result<Response, Error>> {
+    let stream = UnixStream::connect(path).ok()?;
+    let exchange = || -> std::io::Result<String> {
+        stream.set_read_timeout(Some(Duration::from_secs(ANSWER_TIMEOUT_SECONDS)))?;
+        let mut writer = &stream;
+        writeln!(writer, "{}", serde_json::to_string(request)?)?;
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line)?;
+        Ok(line)
+    };
+    let response = exchange()
+        .map_err(|error| Error::Io(format!("Failed to ask the daemon: {}", error)))
+        .and_then(|line| {
+            serde_json::from_str::<Response>(&line)
+                .map_err(|error| Error::Serialization(error.to_string()))
+        })
+        .and_then(|response| match response.error {
+            Some(error) => Err(Error::Daemon(error)),
+            None => Ok(response),
+        });
+    Some(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_format() {
+        let request: Request =
+            serde_json::from_str(r#"{"command": "pause", "for": "2h"}"#).unwrap();
+        assert_eq!(
+            request,
+            Request::Pause {
+                duration: "2h".to_string()
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&Request::Next).unwrap(),
+            r#"{"command":"next"}"#
+        );
+    }
+
+    #[test]
+    fn test_socket_exchange() {
+        let path = std::env::temp_dir().join(format!("prayer-times-{}.sock", std::process::id()));
+        let (sender, messages) = mpsc::channel();
+        serve(&path, sender).unwrap();
+        // A second daemon cannot listen on the same socket
+        assert!(bind(&path).is_err());
+
+        // Daemon answering the requests with their command
+        thread::spawn(move || {
+            while let Ok(Message::Request(request, answer)) = messages.recv() {
+                let response = match request {
+                    Request::Reload => Err(Error::Prayer(prayer_times::Error::ConfigParse(
+                        "Invalid config".to_string(),
+                    ))),
+                    request => Ok(Response::new(format!("{:?}", request), Vec::new())),
+                };
+                let _ = answer.send(Response::from(response));
+            }
+        });
+        let response = request_at(&path, &Request::Today).unwrap().unwrap();
+        assert_eq!(response.text, "Today");
+        let error = request_at(&path, &Request::Reload).unwrap().unwrap_err();
+        assert!(
+            matches!(&error, Error::Daemon(reason) if reason == "Error reading config file: Invalid config")
+        );
+
+        // An unknown command is answered without reaching the daemon
+        let stream = UnixStream::connect(&path).unwrap();
+        writeln!(&stream, r#"{{"command": "dance"}}"#).unwrap();
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line).unwrap();
+        let response: Response = serde_json::from_str(&line).unwrap();
+        assert!(!response.ok);
+        assert!(response.error.unwrap().starts_with("Invalid request: "));
+        std::fs::remove_file(&path).unwrap();
+        assert!(request_at(&path, &Request::Next).is_none());
+    }
+}
@@ -0,0 +1,210 @@
+use crate::event::Event;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Timelike};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// How the iqama time is set from the adhan
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "rule")]
+pub enum IqamaRule {
+    /// Minutes after the adhan
+    After { minutes: u32 },
+    /// Fixed clock time, never before the adhan
+    At { time: NaiveTime },
+    /// At least `min_gap` minutes after the adhan, rounded up to the next multiple of `step` minutes
+    RoundUp { step: u32, min_gap: u32 },
+}
+
+impl IqamaRule {
+    pub fn time(&self, adhan: DateTime<Tz>) -> DateTime<Tz> {
+        match *self {
+            IqamaRule::After { minutes } => adhan + Duration::minutes(minutes as i64),
+            IqamaRule::At { time } => adhan
+                .timezone()
+                .from_local_datetime(&adhan.date_naive().and_time(time))
+                .earliest()
+                .map_or(adhan, |iqama| iqama.max(adhan)),
+            IqamaRule::RoundUp { step, min_gap } => {
+                let earliest = adhan + Duration::minutes(min_gap as i64);
+                let step_seconds = step.max(1) as i64 * 60;
+                let seconds = earliest.num_seconds_from_midnight() as i64;
+                // Seconds to add to reach the next multiple of the step on the clock
+                let missing = (step_seconds - seconds % step_seconds) % step_seconds;
+                earliest + Duration::seconds(missing)
+            }
+        }
+    }
+}
+
+/// Day of the year, without the year
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct MonthDay {
+    pub month: u32,
+    pub day: u32,
+}
+
+impl MonthDay {
+    pub fn of(date: NaiveDate) -> Self {
+        Self {
+            month: date.month(),
+            day: date.day(),
+        }
+    }
+}
+
+impl FromStr for MonthDay {
+    type Err = String;
+
+    /// Parse a day in MM-DD format
+    fn from_str(day_str: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid day '{}', expected MM-DD", day_str);
+        let (month, day) = day_str.split_once('-').ok_or_else(invalid)?;
+        let month: u32 = month.parse().map_err(|_| invalid())?;
+        let day: u32 = day.parse().map_err(|_| invalid())?;
+        // A leap year accepts every day
+        NaiveDate::from_ymd_opt(2000, month, day).ok_or_else(invalid)?;
+        Ok(Self { month, day })
+    }
+}
+
+impl TryFrom<String> for MonthDay {
+    type Error = String;
+
+    fn try_from(day_str: String) -> Result<Self, Self::Error> {
+        day_str.parse()
+    }
+}
+
+impl fmt::Display for MonthDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}-{:02}", self.month, self.day)
+    }
+}
+
+impl From<MonthDay> for String {
+    fn from(month_day: MonthDay) -> Self {
+        month_day.to_string()
+    }
+}
+
+/// Rules used instead of the usual ones between two days of every year (included)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IqamaSeason {
+    pub from: MonthDay,
+    pub to: MonthDay,
+    #[serde(flatten)]
+    pub rules: BTreeMap<Event, IqamaRule>,
+}
+
+impl IqamaSeason {
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        let day = MonthDay::of(date);
+        if self.from <= self.to {
+            self.from <= day && day <= self.to
+        } else {
+            // The season goes over the new year
+            day >= self.from || day <= self.to
+        }
+    }
+}
+
+/// Iqama rules of a mosque
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IqamaSchedule {
+    pub rules: BTreeMap<Event, IqamaRule>,
+    /// The first season containing a day takes precedence over `rules`
+    pub seasons: Vec<IqamaSeason>,
+}
+
+impl IqamaSchedule {
+    pub fn rule(&self, event: Event, date: NaiveDate) -> Option<IqamaRule> {
+        self.seasons
+            .iter()
+            .filter(|season| season.contains(date))
+            .find_map(|season| season.rules.get(&event))
+            .or_else(|| self.rules.get(&event))
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::Europe;
+
+    fn adhan(hour: u32, minute: u32) -> DateTime<Tz> {
+        Europe::Paris
+            .with_ymd_and_hms(2026, 7, 10, hour, minute, 30)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_iqama_rules() {
+        let after = IqamaRule::After { minutes: 20 };
+        assert_eq!(after.time(adhan(4, 10)), adhan(4, 30));
+
+        let at = IqamaRule::At {
+            time: NaiveTime::from_hms_opt(14, 0, 0).unwrap(),
+        };
+        assert_eq!(at.time(adhan(13, 50)).time().to_string(), "14:00:00");
+        // Never before the adhan
+        assert_eq!(at.time(adhan(14, 5)), adhan(14, 5));
+
+        let round_up = IqamaRule::RoundUp {
+            step: 15,
+            min_gap: 10,
+        };
+        assert_eq!(round_up.time(adhan(21, 40)).time().to_string(), "22:00:00");
+        assert_eq!(round_up.time(adhan(21, 34)).time().to_string(), "21:45:00");
+        // Over midnight
+        assert_eq!(
+            round_up.time(adhan(23, 52)).to_rfc3339(),
+            "2026-07-11T00:15:00+02:00"
+        );
+    }
+
+    #[test]
+    fn test_seasons() {
+        let summer = IqamaRule::At {
+            time: NaiveTime::from_hms_opt(14, 0, 0).unwrap(),
+        };
+        let winter = IqamaRule::At {
+            time: NaiveTime::from_hms_opt(13, 0, 0).unwrap(),
+        };
+        let schedule = IqamaSchedule {
+            rules: BTreeMap::from([(Event::Dhuhr, IqamaRule::After { minutes: 15 })]),
+            seasons: vec![
+                IqamaSeason {
+                    from: "06-01".parse().unwrap(),
+                    to: "08-31".parse().unwrap(),
+                    rules: BTreeMap::from([(Event::Dhuhr, summer)]),
+                },
+                IqamaSeason {
+                    from: "12-01".parse().unwrap(),
+                    to: "02-28".parse().unwrap(),
+                    rules: BTreeMap::from([(Event::Dhuhr, winter)]),
+                },
+            ],
+        };
+        let rule = |month, day| {
+            schedule.rule(
+                Event::Dhuhr,
+                NaiveDate::from_ymd_opt(2026, month, day).unwrap(),
+            )
+        };
+
+        assert_eq!(rule(7, 10), Some(summer));
+        assert_eq!(rule(1, 15), Some(winter));
+        assert_eq!(rule(12, 31), Some(winter));
+        assert_eq!(rule(4, 1), Some(IqamaRule::After { minutes: 15 }));
+        assert_eq!(
+            schedule.rule(Event::Asr, NaiveDate::from_ymd_opt(2026, 7, 10).unwrap()),
+            None
+        );
+        assert!("13-01".parse::<MonthDay>().is_err());
+    }
+}
@@ -0,0 +1,27 @@
+use crate::iqama::IqamaRule;
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// Friday prayer settings, the khutbah and iqama times are set from the time of Dhuhr
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Jumuah {
+    /// Report Dhuhr as Jumu'ah on Fridays
+    pub enabled: bool,
+    pub khutbah: Option<IqamaRule>,
+    /// Replaces the usual iqama of Dhuhr on Fridays
+    pub iqama: Option<IqamaRule>,
+}
+
+impl Default for Jumuah {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            khutbah: None,
+            iqama: None,
+        }
+    }
+}
+
+pub fn is_friday(date: NaiveDate) -> bool {
+    date.weekday() == Weekday::Fri
+}
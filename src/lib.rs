@@ -0,0 +1,44 @@
+//! Islamic prayer times calculation
+//!
+//! ```
+//! use prayer_times::{Event, Location, Parameters, Prayer};
+//!
+//! let location = Location { lat: 21.42664, lon: 39.82563 };
+//! let parameters = Parameters::new(location, chrono_tz::Asia::Riyadh);
+//! let date = chrono::NaiveDate::from_ymd_opt(2022, 1, 1).unwrap();
+//! let dhuhr = Prayer::new(Event::Dhuhr, date, &parameters)?;
+//! println!("{}", dhuhr.text_time());
+//! # Ok::<(), prayer_times::Error>(())
+//! ```
+
+pub mod calculations;
+pub mod clock;
+pub mod error;
+pub mod event;
+pub mod high_latitude;
+pub mod hijri;
+pub mod iqama;
+pub mod jumuah;
+pub mod locale;
+pub mod location;
+pub mod madhab;
+pub mod method;
+pub mod parameters;
+pub mod prayer;
+pub mod prayers;
+pub mod qibla;
+
+pub use calculations::AstronomicalMeasures;
+pub use clock::{Clock, FixedClock, ShiftedClock, SystemClock};
+pub use error::Error;
+pub use event::Event;
+pub use high_latitude::HighLatitudeRule;
+pub use hijri::HijriDate;
+pub use iqama::{IqamaRule, IqamaSchedule, IqamaSeason};
+pub use jumuah::Jumuah;
+pub use locale::Locale;
+pub use location::Location;
+pub use madhab::Madhab;
+pub use method::MethodVariant;
+pub use parameters::Parameters;
+pub use prayer::Prayer;
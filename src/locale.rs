@@ -0,0 +1,366 @@
+use crate::event::Event;
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+
+/// Language of the texts given to the user
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Display, EnumString, Serialize, Deserialize,
+)]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Ar,
+    Fr,
+    Tr,
+    Ur,
+    Ms,
+    Id,
+}
+
+/// Texts with placeholders between braces, replaced with [`format_template`]
+pub struct Messages {
+    /// `{event}`, `{time}`
+    pub at: &'static str,
+    /// `{event}`, `{remaining}`
+    pub time_in: &'static str,
+    /// `{event}`, `{remaining}`
+    pub time_since: &'static str,
+    /// `{event}`
+    pub adhan: &'static str,
+    pub jumuah: &'static str,
+    /// `{event}`, `{minutes}`
+    pub reminder_before: &'static str,
+    /// `{event}`, `{minutes}`
+    pub reminder_after: &'static str,
+    /// `{event}`, `{time}`
+    pub missed: &'static str,
+    /// `{event}`
+    pub iqama: &'static str,
+    /// `{event}`, `{time}`
+    pub leave_for_khutbah: &'static str,
+    /// `{event}`, `{time}`
+    pub leave_for_adhan: &'static str,
+    /// `{event}`, `{time}`, `{remaining}`
+    pub next: &'static str,
+    /// `{time}`
+    pub khutbah_at: &'static str,
+    /// `{time}`
+    pub iqama_at: &'static str,
+    pub snooze: &'static str,
+    pub dismiss: &'static str,
+    pub prayed: &'static str,
+}
+
+// Fajr, Sunrise, Dhuhr, Asr, Sunset, Maghrib, Isha, Midnight and Jumu'ah
+type EventNames = [&'static str; 9];
+
+const EN_NAMES: EventNames = [
+    "Fajr", "Sunrise", "Dhuhr", "Asr", "Sunset", "Maghrib", "Isha", "Midnight", "Jumu'ah",
+];
+const EN: Messages = Messages {
+    at: "{event} at {time}",
+    time_in: "{event} in {remaining}",
+    time_since: "{event} since {remaining}",
+    adhan: "Adhan {event}",
+    jumuah: "Jumu'ah Mubarak, time for the Friday prayer",
+    reminder_before: "Adhan {event} in {minutes} minutes",
+    reminder_after: "{event} started {minutes} minutes ago",
+    missed: "Missed {event} at {time}",
+    iqama: "Iqama {event}",
+    leave_for_khutbah: "Time to leave for {event}, khutbah at {time}",
+    leave_for_adhan: "Time to leave for {event}, adhan at {time}",
+    next: "Next: {event} at {time}, in {remaining}",
+    khutbah_at: "Khutbah at {time}",
+    iqama_at: "Iqama at {time}",
+    snooze: "Snooze 5 min",
+    dismiss: "Dismiss",
+    prayed: "Prayed",
+};
+
+const AR_NAMES: EventNames = [
+    "الفجر",
+    "الشروق",
+    "الظهر",
+    "العصر",
+    "الغروب",
+    "المغرب",
+    "العشاء",
+    "منتصف الليل",
+    "الجمعة",
+];
+const AR: Messages = Messages {
+    at: "{event} الساعة {time}",
+    time_in: "{event} بعد {remaining}",
+    time_since: "{event} منذ {remaining}",
+    adhan: "أذان {event}",
+    jumuah: "جمعة مباركة، حان وقت صلاة الجمعة",
+    reminder_before: "أذان {event} بعد {minutes} دقيقة",
+    reminder_after: "دخل وقت {event} منذ {minutes} دقيقة",
+    missed: "فاتتك صلاة {event} الساعة {time}",
+    iqama: "إقامة {event}",
+    leave_for_khutbah: "حان وقت الذهاب إلى {event}، الخطبة الساعة {time}",
+    leave_for_adhan: "حان وقت الذهاب إلى {event}، الأذان الساعة {time}",
+    next: "التالية: {event} الساعة {time}، بعد {remaining}",
+    khutbah_at: "الخطبة الساعة {time}",
+    iqama_at: "الإقامة الساعة {time}",
+    snooze: "تأجيل 5 دقائق",
+    dismiss: "إغلاق",
+    prayed: "صلّيت",
+};
+
+const FR_NAMES: EventNames = [
+    "Fajr",
+    "Lever du soleil",
+    "Dhuhr",
+    "Asr",
+    "Coucher du soleil",
+    "Maghrib",
+    "Isha",
+    "Minuit",
+    "Joumou'a",
+];
+const FR: Messages = Messages {
+    at: "{event} à {time}",
+    time_in: "{event} dans {remaining}",
+    time_since: "{event} depuis {remaining}",
+    adhan: "Adhan {event}",
+    jumuah: "Joumou'a Moubarak, c'est l'heure de la prière du vendredi",
+    reminder_before: "Adhan {event} dans {minutes} minutes",
+    reminder_after: "{event} a commencé il y a {minutes} minutes",
+    missed: "{event} manqué à {time}",
+    iqama: "Iqama {event}",
+    leave_for_khutbah: "Il est temps de partir pour {event}, khoutba à {time}",
+    leave_for_adhan: "Il est temps de partir pour {event}, adhan à {time}",
+    next: "Suivante : {event} à {time}, dans {remaining}",
+    khutbah_at: "Khoutba à {time}",
+    iqama_at: "Iqama à {time}",
+    snooze: "Rappel dans 5 min",
+    dismiss: "Ignorer",
+    prayed: "Prière faite",
+};
+
+const TR_NAMES: EventNames = [
+    "Sabah",
+    "Güneş",
+    "Öğle",
+    "İkindi",
+    "Gün batımı",
+    "Akşam",
+    "Yatsı",
+    "Gece yarısı",
+    "Cuma",
+];
+const TR: Messages = Messages {
+    at: "{event} {time}",
+    time_in: "{event} vaktine {remaining} kaldı",
+    time_since: "{event} vakti {remaining} önce girdi",
+    adhan: "{event} ezanı",
+    jumuah: "Hayırlı Cumalar, Cuma namazı vakti",
+    reminder_before: "{event} ezanına {minutes} dakika kaldı",
+    reminder_after: "{event} vakti {minutes} dakika önce girdi",
+    missed: "Kaçırılan namaz: {event} ({time})",
+    iqama: "{event} kameti",
+    leave_for_khutbah: "{event} için çıkma vakti, hutbe {time}",
+    leave_for_adhan: "{event} için çıkma vakti, ezan {time}",
+    next: "Sonraki: {event} {time}, {remaining} kaldı",
+    khutbah_at: "Hutbe {time}",
+    iqama_at: "Kamet {time}",
+    snooze: "5 dk ertele",
+    dismiss: "Kapat",
+    prayed: "Kılındı",
+};
+
+const UR_NAMES: EventNames = [
+    "فجر",
+    "طلوع آفتاب",
+    "ظہر",
+    "عصر",
+    "غروب آفتاب",
+    "مغرب",
+    "عشاء",
+    "آدھی رات",
+    "جمعہ",
+];
+const UR: Messages = Messages {
+    at: "{event} {time} بجے",
+    time_in: "{event} {remaining} میں",
+    time_since: "{event} کو {remaining} ہو گئے",
+    adhan: "{event} کی اذان",
+    jumuah: "جمعہ مبارک، نمازِ جمعہ کا وقت ہو گیا",
+    reminder_before: "{event} کی اذان {minutes} منٹ میں",
+    reminder_after: "{event} کا وقت {minutes} منٹ پہلے شروع ہوا",
+    missed: "{event} کی نماز {time} بجے چھوٹ گئی",
+    iqama: "{event} کی اقامت",
+    leave_for_khutbah: "{event} کے لیے نکلنے کا وقت، خطبہ {time} بجے",
+    leave_for_adhan: "{event} کے لیے نکلنے کا وقت، اذان {time} بجے",
+    next: "اگلی: {event} {time} بجے، {remaining} میں",
+    khutbah_at: "خطبہ {time} بجے",
+    iqama_at: "اقامت {time} بجے",
+    snooze: "5 منٹ بعد یاد دلائیں",
+    dismiss: "بند کریں",
+    prayed: "پڑھ لی",
+};
+
+const MS_NAMES: EventNames = [
+    "Subuh",
+    "Syuruk",
+    "Zohor",
+    "Asar",
+    "Matahari terbenam",
+    "Maghrib",
+    "Isyak",
+    "Tengah malam",
+    "Jumaat",
+];
+const MS: Messages = Messages {
+    at: "{event} pada {time}",
+    time_in: "{event} dalam {remaining}",
+    time_since: "{event} sejak {remaining}",
+    adhan: "Azan {event}",
+    jumuah: "Jumaat Mubarak, masuk waktu solat Jumaat",
+    reminder_before: "Azan {event} dalam {minutes} minit",
+    reminder_after: "Waktu {event} telah masuk {minutes} minit yang lalu",
+    missed: "Terlepas {event} pada {time}",
+    iqama: "Iqamah {event}",
+    leave_for_khutbah: "Masa untuk ke masjid bagi {event}, khutbah pada {time}",
+    leave_for_adhan: "Masa untuk ke masjid bagi {event}, azan pada {time}",
+    next: "Seterusnya: {event} pada {time}, dalam {remaining}",
+    khutbah_at: "Khutbah pada {time}",
+    iqama_at: "Iqamah pada {time}",
+    snooze: "Tunda 5 minit",
+    dismiss: "Tutup",
+    prayed: "Sudah solat",
+};
+
+const ID_NAMES: EventNames = [
+    "Subuh",
+    "Terbit",
+    "Zuhur",
+    "Asar",
+    "Terbenam",
+    "Magrib",
+    "Isya",
+    "Tengah malam",
+    "Jumat",
+];
+const ID: Messages = Messages {
+    at: "{event} pukul {time}",
+    time_in: "{event} dalam {remaining}",
+    time_since: "{event} sejak {remaining}",
+    adhan: "Azan {event}",
+    jumuah: "Jumat Mubarak, waktunya salat Jumat",
+    reminder_before: "Azan {event} dalam {minutes} menit",
+    reminder_after: "Waktu {event} sudah masuk {minutes} menit yang lalu",
+    missed: "Terlewat {event} pukul {time}",
+    iqama: "Ikamah {event}",
+    leave_for_khutbah: "Waktunya berangkat untuk {event}, khotbah pukul {time}",
+    leave_for_adhan: "Waktunya berangkat untuk {event}, azan pukul {time}",
+    next: "Berikutnya: {event} pukul {time}, dalam {remaining}",
+    khutbah_at: "Khotbah pukul {time}",
+    iqama_at: "Ikamah pukul {time}",
+    snooze: "Tunda 5 menit",
+    dismiss: "Tutup",
+    prayed: "Sudah salat",
+};
+
+impl Locale {
+    /// Locale of the environment (`LC_ALL`, `LC_MESSAGES` then `LANG`), if it is translated
+    pub fn from_env() -> Option<Self> {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(|variable| std::env::var(variable).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Self::from_posix(&value))
+    }
+
+    /// Locale of a POSIX locale name like `fr_FR.UTF-8`
+    pub fn from_posix(name: &str) -> Option<Self> {
+        let language = name.split(['_', '.', '@', '-']).next()?;
+        language.parse().ok()
+    }
+
+    fn names(&self) -> &'static EventNames {
+        match self {
+            Locale::En => &EN_NAMES,
+            Locale::Ar => &AR_NAMES,
+            Locale::Fr => &FR_NAMES,
+            Locale::Tr => &TR_NAMES,
+            Locale::Ur => &UR_NAMES,
+            Locale::Ms => &MS_NAMES,
+            Locale::Id => &ID_NAMES,
+        }
+    }
+
+    pub fn event_name(&self, event: Event) -> &'static str {
+        let index = match event {
+            Event::Fajr => 0,
+            Event::Sunrise => 1,
+            Event::Dhuhr => 2,
+            Event::Asr => 3,
+            Event::Sunset => 4,
+            Event::Maghrib => 5,
+            Event::Isha => 6,
+            Event::Midnight => 7,
+        };
+        self.names()[index]
+    }
+
+    /// Name of the Friday prayer
+    pub fn jumuah_name(&self) -> &'static str {
+        self.names()[8]
+    }
+
+    pub fn messages(&self) -> &'static Messages {
+        match self {
+            Locale::En => &EN,
+            Locale::Ar => &AR,
+            Locale::Fr => &FR,
+            Locale::Tr => &TR,
+            Locale::Ur => &UR,
+            Locale::Ms => &MS,
+            Locale::Id => &ID,
+        }
+    }
+}
+
+/// Replace the `{name}` placeholders of a template, the unknown ones are kept as they are
+pub fn format_template(template: &str, values: &[(&str, &str)]) -> String {
+    values
+        .iter()
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), value)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locale_from_posix() {
+        assert_eq!(Locale::from_posix("fr_FR.UTF-8"), Some(Locale::Fr));
+        assert_eq!(Locale::from_posix("ms_MY"), Some(Locale::Ms));
+        assert_eq!(Locale::from_posix("id"), Some(Locale::Id));
+        assert_eq!(Locale::from_posix("de_DE.UTF-8"), None);
+        assert_eq!(Locale::from_posix("C"), None);
+    }
+
+    #[test]
+    fn test_format_template() {
+        let messages = Locale::Tr.messages();
+        let name = Locale::Tr.event_name(Event::Asr);
+        assert_eq!(
+            format_template(
+                messages.reminder_before,
+                &[("event", name), ("minutes", "10")]
+            ),
+            "İkindi ezanına 10 dakika kaldı"
+        );
+        assert_eq!(
+            format_template("{event} {unknown}", &[("event", "Isha")]),
+            "Isha {unknown}"
+        );
+    }
+}
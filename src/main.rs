@@ -1,69 +1,115 @@
+mod app_error;
 mod arguments;
-mod calculations;
+mod audio;
+mod auto_location;
+mod clock_watch;
 mod config;
 mod daemon;
-mod event;
-mod location;
-mod madhab;
-mod method;
+mod dbus;
+mod hooks;
+mod ics;
+mod ipc;
+mod missed_policy;
 mod notification;
 mod notification_urgency;
-mod prayer;
-mod prayers;
+mod output;
+mod quiet;
+mod reload;
+mod timetable;
 
 use self::{
-    arguments::generation::generate, arguments::Arguments, arguments::Commands, config::Config,
-    madhab::Madhab,
+    arguments::generation::generate,
+    arguments::{
+        month_range, parse_date, parse_hijri_date, parse_now, parse_pause_end, Arguments, Commands,
+        ExportCommands,
+    },
+    config::Config,
+    ipc::{Request, Response},
 };
 
 // TODO Use argument::parse() inside the argument module so we don't include this
+use crate::app_error::Error;
+use chrono::Datelike;
+use chrono_tz::Tz;
 use clap::Parser;
 use daemon::run_daemon;
-use method::MethodVariant;
+use prayer_times::{
+    prayers, Clock, HighLatitudeRule, HijriDate, Madhab, MethodVariant, ShiftedClock, SystemClock,
+};
 
 fn main() {
+    if let Err(error) = run() {
+        eprintln!("Error: {}", error);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<(), Error> {
     let args = Arguments::parse();
 
     let default = Commands::default();
     let command = args.command.as_ref().unwrap_or(&default);
     match command {
         Commands::Daemon(_daemon) => {
-            let config = Config::new(&args);
-            run_daemon(&config);
+            let config = Config::new(&args)?;
+            let clock = clock(&args, config.timezone())?;
+            run_daemon(&args, config, clock)?;
         }
+        Commands::Status => match ipc::request(&Request::Status) {
+            Some(response) => {
+                let response = response?;
+                output::print_answer(&response.text, &response.values, args.format, false)?;
+            }
+            None => println!("The daemon is not running"),
+        },
         Commands::Previous => {
-            let config = Config::new(&args);
-            let prayer = prayers::current(&config).previous();
-            println!("{}", prayer.text_time());
+            let parameters = Config::new(&args)?.parameters();
+            let clock = clock(&args, parameters.timezone)?;
+            let prayer = prayers::current(&parameters, clock.as_ref())?.previous()?;
+            let text = prayer.text_time();
+            output::print_prayer(&prayer, &parameters, clock.as_ref(), args.format, text)?;
         }
         Commands::Current => {
-            let config = Config::new(&args);
-            let prayer = prayers::current(&config);
-            println!("{}", prayer.text_time());
+            if let Some(response) = ask_daemon(&args, Request::Current) {
+                return output::print_answer(&response.text, &response.values, args.format, false);
+            }
+            let parameters = Config::new(&args)?.parameters();
+            let clock = clock(&args, parameters.timezone)?;
+            let prayer = prayers::current(&parameters, clock.as_ref())?;
+            let text = prayer.text_time();
+            output::print_prayer(&prayer, &parameters, clock.as_ref(), args.format, text)?;
         }
         Commands::Next => {
-            let config = Config::new(&args);
-            let prayer = prayers::next(&config);
-            println!("{}", prayer.text_duration());
+            if let Some(response) = ask_daemon(&args, Request::Next) {
+                return output::print_answer(&response.text, &response.values, args.format, false);
+            }
+            let parameters = Config::new(&args)?.parameters();
+            let clock = clock(&args, parameters.timezone)?;
+            let prayer = prayers::next(&parameters, clock.as_ref())?;
+            let text = prayer.text_duration(clock.as_ref());
+            output::print_prayer(&prayer, &parameters, clock.as_ref(), args.format, text)?;
         }
         Commands::Prayers(list_prayers_args) => {
-            let config = Config::new(&args);
+            if list_prayers_args.date.is_none() {
+                if let Some(response) = ask_daemon(&args, Request::Today) {
+                    return output::print_answer(
+                        &response.text,
+                        &response.values,
+                        args.format,
+                        true,
+                    );
+                }
+            }
+            let parameters = Config::new(&args)?.parameters();
+            let clock = clock(&args, parameters.timezone)?;
 
             let prayer_list = if let Some(date_str) = &list_prayers_args.date {
-                match chrono::NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
-                    Ok(date) => prayers::list_prayers_for_date(&config, date),
-                    Err(_) => {
-                        eprintln!("Error: Invalid date format. Please use YYYY-MM-DD format.");
-                        std::process::exit(1);
-                    }
-                }
+                prayers::list_prayers_for_date(&parameters, parse_date(date_str)?)
             } else {
-                prayers::list_prayers(&config)
+                prayers::list_prayers(&parameters, clock.as_ref())
             };
 
-            for prayer in prayer_list {
-                println!("{}", prayer.text_time());
-            }
+            output::print_prayers(prayer_list, &parameters, clock.as_ref(), args.format)?;
         }
         Commands::Methods => {
             MethodVariant::list();
@@ -71,23 +117,110 @@ fn main() {
         Commands::Madhab => {
             Madhab::list_all();
         }
+        Commands::HighLatitude => {
+            HighLatitudeRule::list_all();
+        }
         // Commands::DryRun => {
         //     let config = Config::new(&args);
         //     let next_prayer = prayers::next(&config);
         //     notify_prayer(&next_prayer, &config);
         // }
-        Commands::Config => {
-            let (program, config) = config::config_options();
-            let result = confy::get_configuration_file_path(program, config);
-            match result {
-                Ok(path) => {
-                    println!("Config file: {}", path.display());
-                }
-                Err(e) => {
-                    println!("Error reading config file: {}", e);
-                }
+        Commands::Config => match config::config_file() {
+            Ok(path) => {
+                println!("Config file: {}", path.display());
+            }
+            Err(e) => {
+                println!("Error reading config file: {}", e);
             }
+        },
+        Commands::Export(ExportCommands::Ics(ics_args)) => {
+            let config = Config::new(&args)?;
+            let parameters = config.parameters();
+            let clock = clock(&args, parameters.timezone)?;
+            let today = prayers::current_date(&parameters, clock.as_ref());
+            let (from, to) = ics_args.range.dates((today, today))?;
+            let calendar = ics::calendar(&parameters, from, to, &config.reminders())?;
+            match &ics_args.output {
+                Some(path) => std::fs::write(path, calendar).map_err(|error| {
+                    Error::Io(format!("Failed to write {}: {}", path.display(), error))
+                })?,
+                None => print!("{}", calendar),
+            }
+        }
+        Commands::Timetable(range) => {
+            let parameters = Config::new(&args)?.parameters();
+            let clock = clock(&args, parameters.timezone)?;
+            let today = prayers::current_date(&parameters, clock.as_ref());
+            let (from, to) = range.dates(month_range(today))?;
+            timetable::print_timetable(&parameters, from, to, today, args.format)?;
+        }
+        Commands::Hijri(hijri_args) => {
+            let parameters = Config::new(&args)?.parameters();
+            let clock = clock(&args, parameters.timezone)?;
+            let adjustment = parameters.hijri_adjustment;
+            if let Some(hijri_str) = &hijri_args.from_hijri {
+                let hijri = parse_hijri_date(hijri_str)?;
+                let date = hijri.to_gregorian(adjustment).ok_or(Error::InvalidDate {
+                    date: hijri_str.clone(),
+                    format: "YYYY-MM-DD",
+                })?;
+                output::print_hijri(date, hijri, args.format, date.to_string())?;
+            } else {
+                let (date, hijri) = match &hijri_args.date {
+                    Some(date_str) => {
+                        let date = parse_date(date_str)?;
+                        (date, HijriDate::from_gregorian(date, adjustment))
+                    }
+                    None => {
+                        let now = clock.now().with_timezone(&parameters.timezone);
+                        (now.date_naive(), HijriDate::at(now, &parameters))
+                    }
+                };
+                output::print_hijri(date, hijri, args.format, hijri.to_string())?;
+            }
+        }
+        Commands::Qibla(qibla_args) => {
+            let parameters = Config::new(&args)?.parameters();
+            let clock = clock(&args, parameters.timezone)?;
+            let year = qibla_args
+                .year
+                .unwrap_or_else(|| prayers::current_date(&parameters, clock.as_ref()).year());
+            output::print_qibla(&parameters, qibla_args.declination, year, args.format)?;
+        }
+        Commands::Pause(pause_args) => {
+            let config = Config::new(&args)?;
+            let clock = clock(&args, config.timezone())?;
+            let until = parse_pause_end(clock.now(), &pause_args.duration)?;
+            quiet::pause(&quiet::pause_file(), until)?;
+            println!(
+                "Notifications paused until {}",
+                until
+                    .with_timezone(&config.timezone())
+                    .format("%Y-%m-%d %H:%M")
+            );
+        }
+        Commands::Resume => {
+            quiet::resume(&quiet::pause_file())?;
+            println!("Notifications resumed");
         }
         Commands::GenerateShell => generate(),
     }
+    Ok(())
+}
+
+// Answer of the running daemon, unless the arguments change the prayers it computed. The command
+// computes the prayers itself when the daemon cannot be reached or fails to answer
+fn ask_daemon(args: &Arguments, request: Request) -> Option<Response> {
+    if args.changes_calculation() {
+        return None;
+    }
+    ipc::request(&request)?.ok()
+}
+
+// Clock of the commands, moved to the moment given with `--now`
+fn clock(args: &Arguments, timezone: Tz) -> Result<Box<dyn Clock>, Error> {
+    Ok(match &args.now {
+        Some(now_str) => Box::new(ShiftedClock::starting_at(parse_now(now_str, timezone)?)),
+        None => Box::new(SystemClock),
+    })
 }
@@ -199,6 +199,8 @@ pub const JORDAN: Method = Method {
     },
 };
 
+// The variant names are part of the config file format
+#[allow(clippy::upper_case_acronyms)]
 #[derive(
     Default, Debug, Clone, Copy, PartialEq, EnumString, Serialize, Deserialize, EnumIter, Display,
 )]
@@ -0,0 +1,12 @@
+use serde::Deserialize;
+use serde::Serialize;
+use strum_macros::EnumString;
+
+// What the daemon does with a prayer it could not notify on time (suspend, change of the time)
+#[derive(Default, Debug, Clone, Copy, PartialEq, EnumString, Serialize, Deserialize)]
+pub enum MissedPolicy {
+    Skip,
+    Notify,
+    #[default]
+    NotifyWithin,
+}
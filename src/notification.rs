@@ -1,38 +1,692 @@
-use crate::config::Config;
-use crate::prayer::Prayer;
-use notify_rust::{Notification, Urgency};
-use std::path::PathBuf;
+use crate::app_error::Error;
+use crate::audio::{CurrentPlayback, Playback};
+use crate::config::{Config, NotificationStyle};
+use crate::daemon::Message;
+use chrono::DateTime;
+use chrono_tz::Tz;
+use notify_rust::{Hint, Notification, NotificationHandle, Timeout, Urgency};
+use prayer_times::locale::format_template;
+use prayer_times::prayer::format_duration;
+use prayer_times::{Clock, HijriDate, Locale, Prayer};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use strum_macros::Display;
+use zbus::message::Type;
+use zbus::MatchRule;
 
-fn send_notification(summary: String, urgency: Urgency, icon: PathBuf) {
+// Minutes before a snoozed notification comes back
+pub const SNOOZE_MINUTES: i64 = 5;
+// Name of the desktop entry, so the notification server can group the notifications
+const DESKTOP_ENTRY: &str = env!("CARGO_PKG_NAME");
+// Interface of the notification server, with the signals of the buttons
+const NOTIFICATIONS_INTERFACE: &str = "org.freedesktop.Notifications";
+// Notifications never closed, by a server that forgot them, stop being watched after this
+const MAX_WATCH_SECONDS: u64 = 24 * 60 * 60;
+
+// What a notification is about, for the hooks and the templates
+#[derive(Debug, Clone, Copy, PartialEq, Display, Serialize, Deserialize)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationKind {
+    Adhan,
+    Reminder,
+    Iqama,
+    Missed,
+    Departure,
+}
+
+// Button of a notification picked by the user
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Snooze,
+    Dismiss,
+    Prayed,
+}
+
+// Action picked on the notification of a prayer, sent to the daemon
+pub struct NotificationAction {
+    pub prayer: Prayer,
+    pub action: Action,
+}
+
+fn send_notification(
+    summary: String,
+    body: String,
+    style: NotificationStyle,
+    actions: &[(&str, &str)],
+) -> Result<NotificationHandle, Error> {
+    let urgency: Urgency = style.urgency.unwrap_or_default().into();
     let mut notification = Notification::new(); // so the notification will live
-    let notification = notification.summary(&summary).urgency(urgency);
+    let notification = notification
+        .summary(&summary)
+        .body(&body)
+        .urgency(urgency)
+        .hint(Hint::DesktopEntry(DESKTOP_ENTRY.to_string()));
+    for (identifier, label) in actions {
+        notification.action(identifier, label);
+    }
+    // The low notifications are not worth keeping in the history of the notifications
+    if style.transient.unwrap_or(urgency == Urgency::Low) {
+        notification.hint(Hint::Transient(true));
+    }
+    if style.resident == Some(true) {
+        notification.hint(Hint::Resident(true));
+    }
+    if let Some(category) = style.category {
+        notification.hint(Hint::Category(category));
+    }
+    match style.timeout {
+        Some(0) => notification.timeout(Timeout::Never),
+        Some(seconds) => notification.timeout(Timeout::Milliseconds(seconds * 1000)),
+        None => notification,
+    };
 
-    // ne pas faire un expect mais un simple if suffit
-    let full_path = std::fs::canonicalize(icon).expect("Failed to resolve icon path");
-    if let Some(icon_str) = full_path.to_str() {
-        println!("Setting icon for notification: {}", icon_str);
-        notification.icon(icon_str);
-    } else {
-        println!("Failed to set icon for notification");
+    // A missing icon should not prevent the notification from being sent
+    let icon = style.icon.unwrap_or_default();
+    match std::fs::canonicalize(&icon) {
+        Ok(full_path) => {
+            if let Some(icon_str) = full_path.to_str() {
+                println!("Setting icon for notification: {}", icon_str);
+                notification.icon(icon_str);
+            } else {
+                println!("Failed to set icon for notification");
+            }
+        }
+        Err(error) => eprintln!("Failed to resolve icon path {}: {}", icon.display(), error),
+    }
+
+    let handle = notification
+        .show()
+        .map_err(|error| Error::Notification(error.to_string()))?;
+    println!("Notification sent");
+    Ok(handle)
+}
+
+// Notifications sent by the daemon, replaced in tests. `config` is the current config of the
+// daemon, it changes when the daemon reloads it
+pub trait Notifier {
+    fn prayer(&self, prayer: &Prayer, config: &Config) -> Result<(), Error>;
+    // `offset` is the time from the prayer to the reminder, negative before the prayer
+    fn reminder(
+        &self,
+        prayer: &Prayer,
+        offset: chrono::Duration,
+        config: &Config,
+    ) -> Result<(), Error>;
+    fn missed_prayer(&self, prayer: &Prayer, config: &Config) -> Result<(), Error>;
+    fn iqama(&self, prayer: &Prayer, config: &Config) -> Result<(), Error>;
+    // Time to leave for the Friday prayer
+    fn departure(&self, prayer: &Prayer, config: &Config) -> Result<(), Error>;
+    // The next prayer changed, because the time of `reached` came or because the prayers were
+    // computed again. It is sent even when the notifications are quiet or paused
+    fn next_prayer(&self, next: &Prayer, reached: Option<&Prayer>) -> Result<(), Error>;
+}
+
+// Sends every notification to each notifier, even when one of them fails
+pub struct Notifiers<'a>(pub Vec<Box<dyn Notifier + 'a>>);
+
+impl Notifiers<'_> {
+    fn each(&self, send: impl Fn(&dyn Notifier) -> Result<(), Error>) -> Result<(), Error> {
+        self.0
+            .iter()
+            .map(|notifier| send(notifier.as_ref()))
+            .reduce(Result::and)
+            .unwrap_or(Ok(()))
+    }
+}
+
+impl Notifier for Notifiers<'_> {
+    fn prayer(&self, prayer: &Prayer, config: &Config) -> Result<(), Error> {
+        self.each(|notifier| notifier.prayer(prayer, config))
+    }
+    fn reminder(
+        &self,
+        prayer: &Prayer,
+        offset: chrono::Duration,
+        config: &Config,
+    ) -> Result<(), Error> {
+        self.each(|notifier| notifier.reminder(prayer, offset, config))
+    }
+    fn missed_prayer(&self, prayer: &Prayer, config: &Config) -> Result<(), Error> {
+        self.each(|notifier| notifier.missed_prayer(prayer, config))
+    }
+    fn iqama(&self, prayer: &Prayer, config: &Config) -> Result<(), Error> {
+        self.each(|notifier| notifier.iqama(prayer, config))
     }
+    fn departure(&self, prayer: &Prayer, config: &Config) -> Result<(), Error> {
+        self.each(|notifier| notifier.departure(prayer, config))
+    }
+    fn next_prayer(&self, next: &Prayer, reached: Option<&Prayer>) -> Result<(), Error> {
+        self.each(|notifier| notifier.next_prayer(next, reached))
+    }
+}
+
+// Notification of a prayer waiting for its buttons, with the sound it can stop
+struct Watch {
+    prayer: Prayer,
+    playback: Option<Playback>,
+    stop_on_dismiss: bool,
+    since: Instant,
+}
+
+impl Watch {
+    // Action to send to the daemon for the `identifier` of the button picked, `__closed` when
+    // the notification is closed
+    fn picked(&self, identifier: &str) -> Option<Action> {
+        let action = match identifier {
+            "snooze" => Action::Snooze,
+            "prayed" => Action::Prayed,
+            "dismiss" => Action::Dismiss,
+            "__closed" => {
+                self.stop_audio();
+                return None;
+            }
+            _ => return None,
+        };
+        if action == Action::Dismiss {
+            self.stop_audio();
+        }
+        Some(action)
+    }
+
+    fn stop_audio(&self) {
+        if let Some(playback) = self.playback.as_ref().filter(|_| self.stop_on_dismiss) {
+            playback.stop();
+        }
+    }
+}
+
+// Watched notifications by their id
+type Watches = Arc<Mutex<HashMap<u32, Watch>>>;
+
+pub struct DesktopNotifier<'a> {
+    clock: &'a dyn Clock,
+    // Sound of the adhan, stopped when its notification is closed
+    playback: CurrentPlayback,
+    watches: Watches,
+}
 
-    match notification.show() {
-        Ok(_) => println!("Notification sent"),
-        Err(_) => println!("Failed to send notification"),
+impl<'a> DesktopNotifier<'a> {
+    // The buttons picked on the notifications are sent to `actions`
+    pub fn new(clock: &'a dyn Clock, playback: CurrentPlayback, actions: Sender<Message>) -> Self {
+        let watches = Watches::default();
+        // The notifications are still sent without their buttons
+        if let Err(error) = listen(Arc::clone(&watches), actions) {
+            eprintln!("Failed to listen to the notification buttons: {}", error);
+        }
+        Self {
+            clock,
+            playback,
+            watches,
+        }
+    }
+
+    // Send the actions picked on the notification to the daemon, from the thread listening to
+    // all the notifications
+    fn watch(&self, handle: NotificationHandle, prayer: &Prayer, config: &Config) {
+        let playback: Option<Playback> = self
+            .playback
+            .lock()
+            .ok()
+            .and_then(|current| current.clone());
+        let Ok(mut watches) = self.watches.lock() else {
+            return;
+        };
+        let max_watch = Duration::from_secs(MAX_WATCH_SECONDS);
+        watches.retain(|_, watch| watch.since.elapsed() < max_watch);
+        watches.insert(
+            handle.id(),
+            Watch {
+                prayer: prayer.clone(),
+                playback,
+                stop_on_dismiss: config.stop_audio_on_dismiss(),
+                since: Instant::now(),
+            },
+        );
+    }
+}
+
+// Wait for the buttons and the closing of the watched notifications in a single thread, they
+// are forgotten once closed
+fn listen(watches: Watches, actions: Sender<Message>) -> Result<(), Error> {
+    let dbus_error = |error: zbus::Error| Error::Dbus(error.to_string());
+    let connection = zbus::blocking::Connection::session().map_err(dbus_error)?;
+    let rule = MatchRule::builder()
+        .msg_type(Type::Signal)
+        .interface(NOTIFICATIONS_INTERFACE)
+        .map_err(dbus_error)?
+        .build();
+    let signals = zbus::blocking::MessageIterator::for_match_rule(rule, &connection, None)
+        .map_err(dbus_error)?;
+    std::thread::spawn(move || {
+        for message in signals.flatten() {
+            let header = message.header();
+            let body = message.body();
+            let (id, identifier) = match header.member().map(|member| member.as_str()) {
+                Some("ActionInvoked") => match body.deserialize::<(u32, String)>() {
+                    Ok(signal) => signal,
+                    Err(_) => continue,
+                },
+                Some("NotificationClosed") => match body.deserialize::<(u32, u32)>() {
+                    Ok((id, _reason)) => (id, "__closed".to_string()),
+                    Err(_) => continue,
+                },
+                _ => continue,
+            };
+            let Ok(mut watches) = watches.lock() else {
+                return;
+            };
+            let action = watches.get(&id).and_then(|watch| {
+                let action = watch.picked(&identifier)?;
+                Some(NotificationAction {
+                    prayer: watch.prayer.clone(),
+                    action,
+                })
+            });
+            if identifier == "__closed" {
+                watches.remove(&id);
+            }
+            // The daemon is gone when the channel is closed
+            if let Some(action) = action {
+                if actions.send(Message::Action(Box::new(action))).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+impl Notifier for DesktopNotifier<'_> {
+    fn prayer(&self, prayer: &Prayer, config: &Config) -> Result<(), Error> {
+        let handle = notify_prayer(prayer, config, self.clock)?;
+        self.watch(handle, prayer, config);
+        Ok(())
+    }
+    fn reminder(
+        &self,
+        prayer: &Prayer,
+        offset: chrono::Duration,
+        config: &Config,
+    ) -> Result<(), Error> {
+        let handle = notify_reminder(prayer, offset, config, self.clock)?;
+        self.watch(handle, prayer, config);
+        Ok(())
+    }
+    fn missed_prayer(&self, prayer: &Prayer, config: &Config) -> Result<(), Error> {
+        let handle = notify_missed_prayer(prayer, config, self.clock)?;
+        self.watch(handle, prayer, config);
+        Ok(())
+    }
+    fn iqama(&self, prayer: &Prayer, config: &Config) -> Result<(), Error> {
+        notify_iqama(prayer, config, self.clock)
+    }
+    fn departure(&self, prayer: &Prayer, config: &Config) -> Result<(), Error> {
+        notify_departure(prayer, config, self.clock)
+    }
+    fn next_prayer(&self, _next: &Prayer, _reached: Option<&Prayer>) -> Result<(), Error> {
+        Ok(())
     }
 }
 
 // TODO on a pas besoin de Prayer mais juste du string
-pub fn notify_prayer(prayer: &Prayer, config: &Config) {
-    let summary = format!("Adhan {}", prayer.event());
-    send_notification(summary, config.urgency(), config.icon());
+pub fn notify_prayer(
+    prayer: &Prayer,
+    config: &Config,
+    clock: &dyn Clock,
+) -> Result<NotificationHandle, Error> {
+    let moment = prayer.date_time();
+    let (summary, body) = texts(
+        NotificationKind::Adhan,
+        prayer_summary(prayer, config.locale()),
+        prayer,
+        moment,
+        config,
+        clock,
+    );
+    send_notification(
+        summary,
+        body,
+        config.style(NotificationKind::Adhan, prayer.event()),
+        &prayer_actions(config.locale()),
+    )
 }
 
-pub fn notify_before_prayer(prayer: &Prayer, duration: chrono::Duration, config: &Config) {
-    let summary = format!(
-        "Adhan {} in {} minutes",
-        prayer.event(),
-        duration.num_minutes()
+pub fn notify_reminder(
+    prayer: &Prayer,
+    offset: chrono::Duration,
+    config: &Config,
+    clock: &dyn Clock,
+) -> Result<NotificationHandle, Error> {
+    let summary = reminder_summary(config.locale(), prayer.name(), offset.num_minutes());
+    let (summary, body) = texts(
+        NotificationKind::Reminder,
+        summary,
+        prayer,
+        prayer.date_time(),
+        config,
+        clock,
     );
-    send_notification(summary, Urgency::Low, config.icon());
+    send_notification(
+        summary,
+        body,
+        config.style(NotificationKind::Reminder, prayer.event()),
+        &prayer_actions(config.locale()),
+    )
+}
+
+pub fn prayer_summary(prayer: &Prayer, locale: Locale) -> String {
+    let messages = locale.messages();
+    if prayer.is_jumuah() {
+        messages.jumuah.to_string()
+    } else {
+        format_template(messages.adhan, &[("event", prayer.name())])
+    }
+}
+
+// Text of a reminder `minutes` after the prayer, negative before the prayer
+pub fn reminder_summary(locale: Locale, name: &str, minutes: i64) -> String {
+    let messages = locale.messages();
+    let template = if minutes < 0 {
+        messages.reminder_before
+    } else {
+        messages.reminder_after
+    };
+    let minutes = minutes.abs().to_string();
+    format_template(template, &[("event", name), ("minutes", &minutes)])
+}
+
+pub fn notify_missed_prayer(
+    prayer: &Prayer,
+    config: &Config,
+    clock: &dyn Clock,
+) -> Result<NotificationHandle, Error> {
+    let time = prayer.date_time().format("%H:%M").to_string();
+    let summary = format_template(
+        config.locale().messages().missed,
+        &[("event", prayer.name()), ("time", &time)],
+    );
+    let (summary, body) = texts(
+        NotificationKind::Missed,
+        summary,
+        prayer,
+        prayer.date_time(),
+        config,
+        clock,
+    );
+    send_notification(
+        summary,
+        body,
+        config.style(NotificationKind::Missed, prayer.event()),
+        &prayer_actions(config.locale()),
+    )
+}
+
+pub fn notify_iqama(prayer: &Prayer, config: &Config, clock: &dyn Clock) -> Result<(), Error> {
+    let summary = format_template(
+        config.locale().messages().iqama,
+        &[("event", prayer.name())],
+    );
+    let iqama = prayer.iqama().unwrap_or(prayer.date_time());
+    let (summary, body) = texts(
+        NotificationKind::Iqama,
+        summary,
+        prayer,
+        iqama,
+        config,
+        clock,
+    );
+    let style = config.style(NotificationKind::Iqama, prayer.event());
+    send_notification(summary, body, style, &[]).map(drop)
+}
+
+pub fn notify_departure(prayer: &Prayer, config: &Config, clock: &dyn Clock) -> Result<(), Error> {
+    let khutbah = prayer.khutbah().unwrap_or(prayer.date_time());
+    let (summary, body) = texts(
+        NotificationKind::Departure,
+        departure_summary(prayer, config.locale()),
+        prayer,
+        khutbah,
+        config,
+        clock,
+    );
+    let style = config.style(NotificationKind::Departure, prayer.event());
+    send_notification(summary, body, style, &[]).map(drop)
+}
+
+pub fn departure_summary(prayer: &Prayer, locale: Locale) -> String {
+    let messages = locale.messages();
+    let (template, time) = match prayer.khutbah() {
+        Some(khutbah) => (messages.leave_for_khutbah, khutbah),
+        None => (messages.leave_for_adhan, prayer.date_time()),
+    };
+    let time = time.format("%H:%M").to_string();
+    format_template(template, &[("event", prayer.name()), ("time", &time)])
+}
+
+// Buttons of the notifications of the prayers in the language of the config
+fn prayer_actions(locale: Locale) -> [(&'static str, &'static str); 3] {
+    let messages = locale.messages();
+    [
+        ("snooze", messages.snooze),
+        ("dismiss", messages.dismiss),
+        ("prayed", messages.prayed),
+    ]
+}
+
+// The prayer itself before its time, the one after it once passed
+fn coming_prayer(prayer: &Prayer, clock: &dyn Clock) -> Option<Prayer> {
+    if prayer.time_has_passed(clock) {
+        prayer.next().ok()
+    } else {
+        Some(prayer.clone())
+    }
+}
+
+// Summary and body of a notification from the templates of the config, the texts of the locale
+// are used for the missing ones. `moment` is the time the notification is about
+fn texts(
+    kind: NotificationKind,
+    summary: String,
+    prayer: &Prayer,
+    moment: DateTime<Tz>,
+    config: &Config,
+    clock: &dyn Clock,
+) -> (String, String) {
+    let template = config.template(kind);
+    if template.summary.is_none() && template.body.is_none() {
+        return (summary, body(prayer, config, clock));
+    }
+    let values = placeholders(prayer, moment, config, clock);
+    let values: Vec<(&str, &str)> = values
+        .iter()
+        .map(|(name, value)| (*name, value.as_str()))
+        .collect();
+    let summary = match &template.summary {
+        Some(template) => format_template(template, &values),
+        None => summary,
+    };
+    let body = match &template.body {
+        Some(template) => format_template(template, &values),
+        None => body(prayer, config, clock),
+    };
+    (summary, body)
+}
+
+// Values of the placeholders of the templates
+fn placeholders(
+    prayer: &Prayer,
+    moment: DateTime<Tz>,
+    config: &Config,
+    clock: &dyn Clock,
+) -> Vec<(&'static str, String)> {
+    // Rounded to the closest minute, the notifications are sent a few milliseconds after their time
+    let seconds = (moment.to_utc() - clock.now()).num_seconds().abs();
+    let minutes = (seconds as f64 / 60.).round() as i64;
+    let coming = coming_prayer(prayer, clock);
+    vec![
+        ("event", prayer.name().to_string()),
+        ("time", moment.format("%H:%M").to_string()),
+        ("minutes", minutes.to_string()),
+        (
+            "remaining",
+            coming
+                .as_ref()
+                .map(|coming| format_duration(coming.time_remaining(clock)))
+                .unwrap_or_default(),
+        ),
+        (
+            "next_event",
+            coming
+                .as_ref()
+                .map(|coming| coming.name().to_string())
+                .unwrap_or_default(),
+        ),
+        (
+            "next_time",
+            coming
+                .as_ref()
+                .map(|coming| coming.date_time().format("%H:%M").to_string())
+                .unwrap_or_default(),
+        ),
+        (
+            "hijri",
+            HijriDate::at(prayer.date_time(), &config.parameters()).to_string(),
+        ),
+        ("location_name", config.location_name()),
+    ]
+}
+
+// Times of the Friday prayer, the coming prayer with the time remaining, and the Hijri date
+fn body(prayer: &Prayer, config: &Config, clock: &dyn Clock) -> String {
+    let messages = config.locale().messages();
+    let mut lines: Vec<String> = [
+        (messages.khutbah_at, prayer.khutbah()),
+        (messages.iqama_at, prayer.iqama()),
+    ]
+    .into_iter()
+    .filter(|_| prayer.is_jumuah())
+    .filter_map(|(template, time)| {
+        let time = time?.format("%H:%M").to_string();
+        Some(format_template(template, &[("time", &time)]))
+    })
+    .collect();
+
+    if let Some(coming) = coming_prayer(prayer, clock) {
+        let time = coming.date_time().format("%H:%M").to_string();
+        let remaining = format_duration(coming.time_remaining(clock));
+        lines.push(format_template(
+            messages.next,
+            &[
+                ("event", coming.name()),
+                ("time", &time),
+                ("remaining", &remaining),
+            ],
+        ));
+    }
+
+    lines.push(HijriDate::at(prayer.date_time(), &config.parameters()).to_string());
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prayer_times::{Event, FixedClock};
+    use std::path::Path;
+
+    #[test]
+    fn test_templates() {
+        // The default config with a location, a French locale and a reminder template
+        let mut config = toml::Table::try_from(Config::default()).unwrap();
+        let custom: toml::Table = toml::from_str(
+            r#"
+            location_name = "Paris"
+            locale = "fr"
+            location = { lat = 48.8566, lon = 2.3522 }
+            [reminder]
+            summary = "{event} à {location_name} dans {minutes} min"
+            body = "{event} à {time}, {next_event} dans {remaining}"
+            "#,
+        )
+        .unwrap();
+        for key in ["location_name", "locale", "location"] {
+            config.insert(key.to_string(), custom[key].clone());
+        }
+        let templates =
+            toml::Table::from_iter([("reminder".to_string(), custom["reminder"].clone())]);
+        config["notification"]["templates"] = toml::Value::Table(templates);
+        let config: Config = config.try_into().unwrap();
+
+        let date = chrono::NaiveDate::from_ymd_opt(2026, 3, 18).unwrap();
+        let prayer = Prayer::new(Event::Asr, date, &config.parameters()).unwrap();
+        // 10 minutes before the Asr
+        let clock = FixedClock(prayer.date_time().to_utc() - chrono::Duration::minutes(10));
+
+        let summary = reminder_summary(config.locale(), prayer.name(), -10);
+        assert_eq!(summary, "Adhan Asr dans 10 minutes");
+        let (summary, body) = texts(
+            NotificationKind::Reminder,
+            summary,
+            &prayer,
+            prayer.date_time(),
+            &config,
+            &clock,
+        );
+        assert_eq!(summary, "Asr à Paris dans 10 min");
+        let time = prayer.date_time().format("%H:%M");
+        assert_eq!(body, format!("Asr à {}, Asr dans 00H10", time));
+
+        // The texts of the locale without templates
+        let (summary, body) = texts(
+            NotificationKind::Adhan,
+            prayer_summary(&prayer, config.locale()),
+            &prayer,
+            prayer.date_time(),
+            &config,
+            &clock,
+        );
+        assert_eq!(summary, "Adhan Asr");
+        assert!(body.starts_with(&format!("Suivante : Asr à {}, dans 00H10", time)));
+    }
+
+    #[test]
+    fn test_dismiss_stops_the_audio_when_enabled() {
+        let config = crate::config::test_config("location = { lat = 48.8566, lon = 2.3522 }");
+        let date = chrono::NaiveDate::from_ymd_opt(2026, 3, 18).unwrap();
+        let prayer = Prayer::new(Event::Asr, date, &config.parameters()).unwrap();
+        let watch = |stop_on_dismiss| Watch {
+            prayer: prayer.clone(),
+            playback: Some(crate::audio::play("sleep {file}", Path::new("10"), 100).unwrap()),
+            stop_on_dismiss,
+            since: Instant::now(),
+        };
+        let stopped = |watch: &Watch| {
+            let playback = watch.playback.as_ref().unwrap();
+            (0..100).any(|_| {
+                std::thread::sleep(Duration::from_millis(10));
+                !playback.is_playing()
+            })
+        };
+
+        // The adhan keeps playing without `stop_on_dismiss`
+        let playing = watch(false);
+        assert_eq!(playing.picked("dismiss"), Some(Action::Dismiss));
+        assert_eq!(playing.picked("__closed"), None);
+        assert!(playing.playback.as_ref().unwrap().is_playing());
+        playing.playback.as_ref().unwrap().stop();
+
+        let dismissed = watch(true);
+        assert_eq!(dismissed.picked("snooze"), Some(Action::Snooze));
+        assert!(dismissed.playback.as_ref().unwrap().is_playing());
+        assert_eq!(dismissed.picked("dismiss"), Some(Action::Dismiss));
+        assert!(stopped(&dismissed));
+
+        let closed = watch(true);
+        assert_eq!(closed.picked("__closed"), None);
+        assert!(stopped(&closed));
+    }
 }
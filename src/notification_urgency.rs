@@ -3,7 +3,7 @@ use serde::Deserialize;
 use serde::Serialize;
 use strum_macros::EnumString;
 
-#[derive(Default, Debug, Clone, EnumString, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone, PartialEq, EnumString, Serialize, Deserialize)]
 pub enum NotifUrgency {
     Low,
     Normal,
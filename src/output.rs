@@ -0,0 +1,416 @@
+use crate::app_error::Error;
+use chrono::{DateTime, NaiveDate};
+use chrono_tz::Tz;
+use prayer_times::locale::format_template;
+use prayer_times::Error as PrayerError;
+use prayer_times::{
+    qibla, Clock, Event, HijriDate, Location, Madhab, MethodVariant, Parameters, Prayer,
+};
+use serde::Serialize;
+use serde_json::Value;
+use strum_macros::{Display, EnumString};
+
+// Shown instead of the time of an event that doesn't happen (polar day or night)
+pub const UNDEFINED_TIME: &str = "--:--";
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, EnumString, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Plain,
+    Json,
+    Csv,
+    Tsv,
+}
+
+// A prayer with the parameters used to calculate it, without times when the event doesn't happen
+#[derive(Serialize)]
+struct PrayerOutput<'a> {
+    event: Event,
+    name: &'a str,
+    datetime: Option<DateTime<Tz>>,
+    date: NaiveDate,
+    iqama: Option<DateTime<Tz>>,
+    khutbah: Option<DateTime<Tz>>,
+    hijri: String,
+    remaining_seconds: Option<i64>,
+    method: MethodVariant,
+    madhab: &'a Madhab,
+    #[serde(flatten)]
+    location: &'a Location,
+    timezone: &'a str,
+}
+
+pub fn to_value(
+    prayer: &Prayer,
+    parameters: &Parameters,
+    clock: &dyn Clock,
+) -> Result<Value, Error> {
+    let output = PrayerOutput {
+        event: prayer.event(),
+        name: prayer.name(),
+        datetime: Some(prayer.date_time()),
+        date: prayer.date_time().date_naive(),
+        iqama: prayer.iqama(),
+        khutbah: prayer.khutbah(),
+        hijri: HijriDate::at(prayer.date_time(), parameters).to_string(),
+        remaining_seconds: Some(
+            prayer
+                .date_time()
+                .signed_duration_since(clock.now())
+                .num_seconds(),
+        ),
+        method: parameters.method,
+        madhab: &parameters.madhab,
+        location: &parameters.location,
+        timezone: parameters.timezone.name(),
+    };
+    serde_json::to_value(output).map_err(|error| Error::Serialization(error.to_string()))
+}
+
+// Values of the prayers of a day, with the same fields and null times for the undefined events
+pub fn to_values(
+    prayers: impl IntoIterator<Item = Result<Prayer, PrayerError>>,
+    parameters: &Parameters,
+    clock: &dyn Clock,
+) -> Result<Vec<Value>, Error> {
+    prayers
+        .into_iter()
+        .map(|prayer| match prayer {
+            Ok(prayer) => to_value(&prayer, parameters, clock),
+            Err(PrayerError::UndefinedCalculation { event, date }) => {
+                let output = PrayerOutput {
+                    event,
+                    name: parameters.locale.event_name(event),
+                    datetime: None,
+                    date,
+                    iqama: None,
+                    khutbah: None,
+                    hijri: HijriDate::from_gregorian(date, parameters.hijri_adjustment).to_string(),
+                    remaining_seconds: None,
+                    method: parameters.method,
+                    madhab: &parameters.madhab,
+                    location: &parameters.location,
+                    timezone: parameters.timezone.name(),
+                };
+                serde_json::to_value(output)
+                    .map_err(|error| Error::Serialization(error.to_string()))
+            }
+            Err(error) => Err(error.into()),
+        })
+        .collect()
+}
+
+// Print a single prayer, `text` is used for the plain format
+pub fn print_prayer(
+    prayer: &Prayer,
+    parameters: &Parameters,
+    clock: &dyn Clock,
+    format: OutputFormat,
+    text: String,
+) -> Result<(), Error> {
+    if format == OutputFormat::Plain {
+        println!("{}", text);
+        return Ok(());
+    }
+    print_values(&[to_value(prayer, parameters, clock)?], format, false)
+}
+
+pub fn print_prayers(
+    prayers: [Result<Prayer, PrayerError>; 8],
+    parameters: &Parameters,
+    clock: &dyn Clock,
+    format: OutputFormat,
+) -> Result<(), Error> {
+    if format == OutputFormat::Plain {
+        println!("{}", prayers_text(&prayers, parameters));
+        return Ok(());
+    }
+    print_values(&to_values(prayers, parameters, clock)?, format, true)
+}
+
+// Plain output of the prayers of a day, with the date and the times of the iqama and the khutbah
+pub fn prayers_text(prayers: &[Result<Prayer, PrayerError>], parameters: &Parameters) -> String {
+    let mut lines = Vec::new();
+    if let Some(first) = prayers.iter().flatten().next() {
+        let date = first.date_time().date_naive();
+        let hijri = HijriDate::from_gregorian(date, parameters.hijri_adjustment);
+        lines.push(format!("{} - {}", date.format("%A %d %B %Y"), hijri));
+    }
+    let messages = parameters.locale.messages();
+    for prayer in prayers {
+        let prayer = match prayer {
+            Ok(prayer) => prayer,
+            Err(PrayerError::UndefinedCalculation { event, .. }) => {
+                let name = parameters.locale.event_name(*event);
+                let values = [("event", name), ("time", UNDEFINED_TIME)];
+                lines.push(format_template(messages.at, &values));
+                continue;
+            }
+            Err(error) => {
+                lines.push(error.to_string());
+                continue;
+            }
+        };
+        let times: Vec<String> = [
+            (messages.khutbah_at, prayer.khutbah()),
+            (messages.iqama_at, prayer.iqama()),
+        ]
+        .into_iter()
+        .filter_map(|(template, time)| {
+            let time = time?.time().to_string();
+            Some(format_template(template, &[("time", &time)]))
+        })
+        .collect();
+        if times.is_empty() {
+            lines.push(prayer.text_time());
+        } else {
+            lines.push(format!("{} ({})", prayer.text_time(), times.join(", ")));
+        }
+    }
+    lines.join("\n")
+}
+
+// A gregorian date with its Hijri date
+#[derive(Serialize)]
+struct HijriOutput {
+    date: NaiveDate,
+    #[serde(flatten)]
+    hijri: HijriDate,
+    month_name: &'static str,
+}
+
+// Print a date conversion, `text` is used for the plain format
+pub fn print_hijri(
+    date: NaiveDate,
+    hijri: HijriDate,
+    format: OutputFormat,
+    text: String,
+) -> Result<(), Error> {
+    if format == OutputFormat::Plain {
+        println!("{}", text);
+        return Ok(());
+    }
+    let output = HijriOutput {
+        date,
+        hijri,
+        month_name: hijri.month_name(),
+    };
+    let value =
+        serde_json::to_value(output).map_err(|error| Error::Serialization(error.to_string()))?;
+    print_values(&[value], format, false)
+}
+
+// Direction of the Kaaba from a location
+#[derive(Serialize)]
+struct QiblaOutput<'a> {
+    #[serde(flatten)]
+    location: &'a Location,
+    bearing: f64,
+    magnetic_bearing: Option<f64>,
+    compass_point: &'static str,
+    distance_km: f64,
+    qibla_days: Vec<DateTime<Tz>>,
+}
+
+pub fn print_qibla(
+    parameters: &Parameters,
+    declination: Option<f64>,
+    year: i32,
+    format: OutputFormat,
+) -> Result<(), Error> {
+    let location = &parameters.location;
+    let output = QiblaOutput {
+        location,
+        bearing: qibla::bearing(location),
+        magnetic_bearing: declination
+            .map(|declination| qibla::magnetic_bearing(location, declination)),
+        compass_point: qibla::compass_point(qibla::bearing(location)),
+        distance_km: qibla::distance(location),
+        qibla_days: qibla::qibla_days(year, parameters.timezone),
+    };
+
+    if format == OutputFormat::Plain {
+        println!(
+            "Qibla at {:.1}° from true north ({})",
+            output.bearing, output.compass_point
+        );
+        if let (Some(magnetic_bearing), Some(declination)) = (output.magnetic_bearing, declination)
+        {
+            println!(
+                "Qibla at {:.1}° from magnetic north (declination {}°)",
+                magnetic_bearing, declination
+            );
+        }
+        println!("Kaaba at {:.0} km", output.distance_km);
+        println!(
+            "Qibla days of {}, the sun is over the Kaaba and the shadows point away from it:",
+            year
+        );
+        for date_time in &output.qibla_days {
+            println!("  {}", date_time.format("%A %d %B at %H:%M"));
+        }
+        return Ok(());
+    }
+    let value =
+        serde_json::to_value(output).map_err(|error| Error::Serialization(error.to_string()))?;
+    print_values(&[value], format, false)
+}
+
+// Print an answer of the daemon, `text` is used for the plain format
+pub fn print_answer(
+    text: &str,
+    values: &[Value],
+    format: OutputFormat,
+    is_list: bool,
+) -> Result<(), Error> {
+    if format == OutputFormat::Plain {
+        println!("{}", text);
+        return Ok(());
+    }
+    print_values(values, format, is_list)
+}
+
+// Print serialized objects, as a json array if `is_list` is true
+pub fn print_values(values: &[Value], format: OutputFormat, is_list: bool) -> Result<(), Error> {
+    match format {
+        OutputFormat::Plain | OutputFormat::Json => {
+            if is_list {
+                println!("{:#}", Value::Array(values.to_vec()));
+            } else if let Some(value) = values.first() {
+                println!("{:#}", value);
+            }
+        }
+        OutputFormat::Csv => print_table(values, ','),
+        OutputFormat::Tsv => print_table(values, '\t'),
+    }
+    Ok(())
+}
+
+// Print objects as rows, with their keys as header
+fn print_table(values: &[Value], separator: char) {
+    for line in table(values, separator) {
+        println!("{}", line);
+    }
+}
+
+fn table(values: &[Value], separator: char) -> Vec<String> {
+    let Some(Value::Object(first)) = values.first() else {
+        return Vec::new();
+    };
+    let header: Vec<String> = first
+        .keys()
+        .map(|key| escape_field(key, separator))
+        .collect();
+    let mut lines = vec![header.join(&separator.to_string())];
+
+    for value in values {
+        let Value::Object(object) = value else {
+            continue;
+        };
+        let row: Vec<String> = object
+            .values()
+            .map(|field| match field {
+                Value::String(text) => escape_field(text, separator),
+                Value::Null => String::new(),
+                other => escape_field(&other.to_string(), separator),
+            })
+            .collect();
+        lines.push(row.join(&separator.to_string()));
+    }
+    lines
+}
+
+fn escape_field(field: &str, separator: char) -> String {
+    if separator == '\t' {
+        // TSV has no quoting, tabs and new lines are replaced by spaces
+        return field.replace(['\t', '\n', '\r'], " ");
+    }
+    if field.contains([separator, '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prayer_times::{prayers, FixedClock};
+
+    #[test]
+    fn test_table_escaping() {
+        let values = [serde_json::json!({
+            "name": "Dhuhr, Jumu'ah",
+            "note": "the \"khutbah\"\nat 13:30",
+            "place": "Paris\tcentre",
+            "iqama": null,
+        })];
+        assert_eq!(
+            table(&values, ','),
+            [
+                "name,note,place,iqama",
+                "\"Dhuhr, Jumu'ah\",\"the \"\"khutbah\"\"\nat 13:30\",Paris\tcentre,",
+            ]
+        );
+        // TSV has no quoting
+        assert_eq!(
+            table(&values, '\t')[1],
+            "Dhuhr, Jumu'ah\tthe \"khutbah\" at 13:30\tParis centre\t"
+        );
+    }
+
+    #[test]
+    fn test_json_fields() {
+        let parameters = Parameters::new(
+            Location {
+                lat: 48.8566,
+                lon: 2.3522,
+            },
+            chrono_tz::Europe::Paris,
+        );
+        let date = NaiveDate::from_ymd_opt(2026, 3, 18).unwrap();
+        let prayer = Prayer::new(Event::Asr, date, &parameters).unwrap();
+        let clock = FixedClock(prayer.date_time().to_utc() - chrono::Duration::minutes(10));
+
+        let value = to_value(&prayer, &parameters, &clock).unwrap();
+        let keys: Vec<&str> = value
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(keys[..4], ["event", "name", "datetime", "date"]);
+        assert_eq!(value["event"], "Asr");
+        assert_eq!(value["name"], "Asr");
+        assert_eq!(value["datetime"], prayer.date_time().to_rfc3339());
+        assert_eq!(value["remaining_seconds"], 600);
+    }
+
+    #[test]
+    fn test_undefined_events() {
+        // Polar night in Tromsø, the sun doesn't rise
+        let parameters = Parameters::new(
+            Location {
+                lat: 69.6492,
+                lon: 18.9553,
+            },
+            chrono_tz::Europe::Oslo,
+        );
+        let date = NaiveDate::from_ymd_opt(2025, 12, 21).unwrap();
+        let prayers = prayers::list_prayers_for_date(&parameters, date);
+        let clock = FixedClock(date.and_hms_opt(12, 0, 0).unwrap().and_utc());
+
+        let text = prayers_text(&prayers, &parameters);
+        assert!(text.contains("Sunrise at --:--"));
+        assert!(text.contains("Dhuhr at "));
+        let values = to_values(prayers, &parameters, &clock).unwrap();
+        assert_eq!(values.len(), 8);
+        assert!(values[1]["datetime"].is_null());
+        assert!(values[1]["remaining_seconds"].is_null());
+        assert!(values[2]["datetime"].is_string());
+        // Same columns for every event
+        let keys = |index: usize| values[index].as_object().unwrap().keys().count();
+        assert_eq!(keys(1), keys(2));
+    }
+}
@@ -0,0 +1,124 @@
+use crate::event::Event;
+use crate::high_latitude::HighLatitudeRule;
+use crate::iqama::IqamaSchedule;
+use crate::jumuah::Jumuah;
+use crate::locale::Locale;
+use crate::location::Location;
+use crate::madhab::Madhab;
+use crate::method::{MethodVariant, ParamValue};
+use chrono::{NaiveDate, NaiveTime, TimeZone};
+use chrono_tz::{OffsetComponents, Tz};
+
+/// Everything needed to calculate the prayer times of a place
+#[derive(Debug, Clone)]
+pub struct Parameters {
+    pub location: Location,
+    pub timezone: Tz,
+    pub method: MethodVariant,
+    pub madhab: Madhab,
+    pub high_latitude: HighLatitudeRule,
+    /// Minutes to add or remove to the Fajr time
+    pub fajr_mod: i8,
+    /// Minutes to add or remove to the Dhuhr time
+    pub dhuhr_mod: i8,
+    /// Minutes to add or remove to the Asr time
+    pub asr_mod: i8,
+    /// Minutes to add or remove to the Maghrib time
+    pub maghrib_mod: i8,
+    /// Minutes to add or remove to the Isha time
+    pub isha_mod: i8,
+    /// Days to add or remove to the Hijri date for the local moon sighting
+    pub hijri_adjustment: i8,
+    /// Iqama rules, for the prayers that have one
+    pub iqama: IqamaSchedule,
+    pub jumuah: Jumuah,
+    /// Language of the names and texts of the prayers
+    pub locale: Locale,
+}
+
+impl Parameters {
+    /// Parameters with the default method, madhab and high latitude rule
+    pub fn new(location: Location, timezone: Tz) -> Self {
+        Self {
+            location,
+            timezone,
+            method: MethodVariant::default(),
+            madhab: Madhab::default(),
+            high_latitude: HighLatitudeRule::default(),
+            fajr_mod: 0,
+            dhuhr_mod: 0,
+            asr_mod: 0,
+            maghrib_mod: 0,
+            isha_mod: 0,
+            hijri_adjustment: 0,
+            iqama: IqamaSchedule::default(),
+            jumuah: Jumuah::default(),
+            locale: Locale::default(),
+        }
+    }
+
+    pub fn lat(&self) -> f64 {
+        self.location.lat
+    }
+    pub fn lon(&self) -> f64 {
+        self.location.lon
+    }
+
+    /// UTC offset of the timezone in minutes, for the given date (takes DST into account)
+    pub fn timezone_offset(&self, date: NaiveDate) -> i64 {
+        // Offset at local noon, DST transitions happen during the night
+        let noon = date.and_time(NaiveTime::from_hms_opt(12, 0, 0).unwrap_or_default());
+        let offset = self
+            .timezone
+            .offset_from_local_datetime(&noon)
+            .earliest()
+            .unwrap_or_else(|| self.timezone.offset_from_utc_datetime(&noon));
+        let total_offset = offset.base_utc_offset() + offset.dst_offset();
+        total_offset.num_minutes()
+    }
+
+    pub fn fajr_param(&self) -> ParamValue {
+        self.method.get().params.fajr
+    }
+    pub fn isha_param(&self) -> ParamValue {
+        self.method.get().params.isha
+    }
+    pub fn shadow_multiplier(&self) -> u8 {
+        self.madhab.shadow_multiplier()
+    }
+
+    /// Adjustment of the event time in hours
+    pub fn offset(&self, event: Event) -> f64 {
+        let minutes_mod = match event {
+            Event::Fajr => self.fajr_mod,
+            Event::Sunrise => 0,
+            Event::Dhuhr => self.dhuhr_mod,
+            Event::Asr => self.asr_mod,
+            Event::Sunset => 0,
+            Event::Maghrib => self.maghrib_mod,
+            Event::Isha => self.isha_mod,
+            Event::Midnight => 0,
+        };
+        minutes_mod as f64 / 60.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offset_at_local_noon() {
+        // Honolulu moved from UTC-10:30 to UTC-9:30 at 2:00, 12:00 UTC was still before
+        let parameters = Parameters::new(
+            Location {
+                lat: 21.3069,
+                lon: -157.8583,
+            },
+            chrono_tz::Pacific::Honolulu,
+        );
+        let date = NaiveDate::from_ymd_opt(1933, 4, 30).unwrap();
+        assert_eq!(parameters.timezone_offset(date), -570);
+        assert_eq!(parameters.timezone_offset(date.pred_opt().unwrap()), -630);
+    }
+}
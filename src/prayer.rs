@@ -1,13 +1,27 @@
 use crate::calculations::AstronomicalMeasures;
+use crate::clock::Clock;
+use crate::error::{Error, Result};
 use crate::event::Event;
-use crate::Config;
-use chrono::{Days, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime};
+use crate::jumuah::is_friday;
+use crate::locale::format_template;
+use crate::parameters::Parameters;
+use chrono::{DateTime, Days, Duration, NaiveDate, NaiveTime};
+use chrono_tz::Tz;
+use serde::Serialize;
 
+#[derive(Clone, Serialize)]
 pub struct Prayer {
     event: Event,
-    date: NaiveDateTime,
+    // Name in the locale of the parameters, Jumu'ah for Dhuhr on Fridays
+    name: String,
+    #[serde(skip)]
+    is_jumuah: bool,
+    #[serde(rename = "datetime")]
+    date: DateTime<Tz>,
+    #[serde(skip)]
     measures: AstronomicalMeasures,
-    config: Config,
+    #[serde(skip)]
+    parameters: Parameters,
 }
 impl PartialEq for Prayer {
     fn eq(&self, other: &Self) -> bool {
@@ -15,67 +29,102 @@ impl PartialEq for Prayer {
     }
 }
 impl Prayer {
-    fn new_from_measures(event: Event, measures: AstronomicalMeasures, config: &Config) -> Prayer {
-        Self {
+    fn new_from_measures(
+        event: Event,
+        measures: AstronomicalMeasures,
+        parameters: &Parameters,
+    ) -> Result<Prayer> {
+        let is_jumuah =
+            parameters.jumuah.enabled && event == Event::Dhuhr && is_friday(measures.date());
+        let name = if is_jumuah {
+            parameters.locale.jumuah_name()
+        } else {
+            parameters.locale.event_name(event)
+        };
+        Ok(Self {
             event,
-            date: measures.date_time(event),
+            name: name.to_string(),
+            is_jumuah,
+            date: measures.date_time(event)?,
             measures,
-            config: config.clone(),
-        }
+            parameters: parameters.clone(),
+        })
     }
-    pub fn new(event: Event, date: NaiveDate, config: &Config) -> Prayer {
-        let measures = AstronomicalMeasures::new(date, config);
-        Self::new_from_measures(event, measures, config)
+    pub fn new(event: Event, date: NaiveDate, parameters: &Parameters) -> Result<Prayer> {
+        let measures = AstronomicalMeasures::new(date, parameters);
+        Self::new_from_measures(event, measures, parameters)
     }
 
-    fn new_from_date(&self, event: Event) -> Prayer {
-        Self::new_from_measures(event, self.measures.clone(), &self.config)
+    fn new_from_date(&self, event: Event) -> Result<Prayer> {
+        Self::new_from_measures(event, self.measures.clone(), &self.parameters)
     }
 
     pub fn event(&self) -> Event {
         self.event
     }
-    pub fn date_time(&self) -> NaiveDateTime {
-        self.date
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Whether this is the Friday prayer
+    pub fn is_jumuah(&self) -> bool {
+        self.is_jumuah
     }
-    pub fn date(&self) -> NaiveDate {
-        self.date.date()
+    pub fn date_time(&self) -> DateTime<Tz> {
+        self.date
     }
     pub fn time(&self) -> NaiveTime {
         self.date.time()
     }
+    /// Time of the iqama, when the parameters have a rule for this prayer
+    pub fn iqama(&self) -> Option<DateTime<Tz>> {
+        let friday_rule = self.parameters.jumuah.iqama.filter(|_| self.is_jumuah());
+        friday_rule
+            .or_else(|| self.parameters.iqama.rule(self.event, self.measures.date()))
+            .map(|rule| rule.time(self.date))
+    }
+    /// Time of the khutbah of the Friday prayer, when the parameters have a rule for it
+    pub fn khutbah(&self) -> Option<DateTime<Tz>> {
+        let rule = self
+            .parameters
+            .jumuah
+            .khutbah
+            .filter(|_| self.is_jumuah())?;
+        Some(rule.time(self.date))
+    }
 
-    pub fn previous(&self) -> Prayer {
-        let previous_prayer = self.new_from_date(self.event.previous());
-        if previous_prayer.time() <= self.time() {
-            return previous_prayer;
+    pub fn previous(&self) -> Result<Prayer> {
+        let previous_prayer = self.new_from_date(self.event.previous())?;
+        if previous_prayer.date_time() <= self.date_time() {
+            return Ok(previous_prayer);
         }
 
-        let previous_date = self
-            .date()
-            .checked_sub_days(Days::new(1))
-            .expect("Overflow when subtracting days");
-        Self::new(self.event.previous(), previous_date, &self.config)
+        let previous_date = self.measures.date().checked_sub_days(Days::new(1)).ok_or(
+            Error::UndefinedCalculation {
+                event: self.event.previous(),
+                date: self.measures.date(),
+            },
+        )?;
+        Self::new(self.event.previous(), previous_date, &self.parameters)
     }
 
-    pub fn next(&self) -> Prayer {
-        let next_prayer = self.new_from_date(self.event.next());
-        if next_prayer.date_time().time() >= self.date_time().time() {
-            return next_prayer;
+    pub fn next(&self) -> Result<Prayer> {
+        let next_prayer = self.new_from_date(self.event.next())?;
+        if next_prayer.date_time() >= self.date_time() {
+            return Ok(next_prayer);
         }
 
-        let next_date = self
-            .date()
-            .checked_add_days(Days::new(1))
-            .expect("Overflow when adding days");
-        Self::new(self.event.next(), next_date, &self.config)
+        let next_date = self.measures.date().checked_add_days(Days::new(1)).ok_or(
+            Error::UndefinedCalculation {
+                event: self.event.next(),
+                date: self.measures.date(),
+            },
+        )?;
+        Self::new(self.event.next(), next_date, &self.parameters)
     }
 
     // Returns the time remaining for the next prayer to happen
-    pub fn time_remaining(&self) -> Duration {
-        let duration = self
-            .date_time()
-            .signed_duration_since(Local::now().naive_local());
+    pub fn time_remaining(&self, clock: &dyn Clock) -> Duration {
+        let duration = self.date_time().signed_duration_since(clock.now());
 
         // The time remaining should not be negative
         if duration < Duration::zero() {
@@ -85,27 +134,36 @@ impl Prayer {
     }
 
     // Returns true if the time of the prayer passed
-    pub fn time_has_passed(&self) -> bool {
-        self.time_remaining() <= Duration::zero()
+    pub fn time_has_passed(&self, clock: &dyn Clock) -> bool {
+        self.time_remaining(clock) <= Duration::zero()
     }
 
-    pub fn text_duration(&self) -> String {
-        let time_remaining = self.time_remaining();
-        let in_or_since = if self.time_has_passed() {
-            "since"
+    pub fn text_duration(&self, clock: &dyn Clock) -> String {
+        let messages = self.parameters.locale.messages();
+        let template = if self.time_has_passed(clock) {
+            messages.time_since
         } else {
-            "in"
+            messages.time_in
         };
-
-        format!(
-            "{} {in_or_since} {:02}H{:02}",
-            self.event(),
-            time_remaining.num_hours(),
-            time_remaining.num_minutes() % 60
+        let remaining = format_duration(self.time_remaining(clock));
+        format_template(
+            template,
+            &[("event", self.name()), ("remaining", &remaining)],
         )
     }
     pub fn text_time(&self) -> String {
-        format!("{} at {}", self.event(), self.time())
+        let time = self.time().to_string();
+        let messages = self.parameters.locale.messages();
+        format_template(messages.at, &[("event", self.name()), ("time", &time)])
         // format!("{} at {} the {}", self.event(), self.time(), self.date())
     }
 }
+
+/// Duration in the HHhMM format of the prayer texts
+pub fn format_duration(duration: Duration) -> String {
+    format!(
+        "{:02}H{:02}",
+        duration.num_hours(),
+        duration.num_minutes() % 60
+    )
+}
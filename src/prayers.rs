@@ -1,196 +1,265 @@
+use crate::clock::Clock;
+use crate::error::{Error, Result};
 use crate::event::Event;
+use crate::parameters::Parameters;
 use crate::prayer::Prayer;
-use crate::Config;
-use chrono::{Local, NaiveDate};
+use chrono::{DateTime, Days, NaiveDate, Utc};
+
+/// Last prayer that happened before now, skipping the events that don't happen like `next_at`.
+/// Sunset and Midnight are not prayers, the current prayer stays the one before them
+pub fn current(parameters: &Parameters, clock: &dyn Clock) -> Result<Prayer> {
+    let now = clock.now();
+    let mut candidates: Vec<Result<Prayer>> = candidates(parameters, now).collect();
+    candidates.reverse();
+    let is_prayer = |prayer: &Prayer| !matches!(prayer.event(), Event::Sunset | Event::Midnight);
+    first_defined(
+        candidates,
+        |prayer| is_prayer(prayer) && prayer.date_time() < now,
+        parameters,
+        now,
+    )
+}
+
+/// Current date in the timezone of the parameters
+pub fn current_date(parameters: &Parameters, clock: &dyn Clock) -> NaiveDate {
+    clock.now().with_timezone(&parameters.timezone).date_naive()
+}
 
-pub fn current(config: &Config) -> Prayer {
-    next(config).previous()
+pub fn next(parameters: &Parameters, clock: &dyn Clock) -> Result<Prayer> {
+    next_at(parameters, clock.now())
 }
 
-pub fn next(config: &Config) -> Prayer {
-    let current_date = Local::now().date_naive();
-    let current_time = Local::now().time();
+/// First prayer happening at or after `now`. The events that don't happen at this location
+/// (polar day or night) are skipped, it fails only when none of them happens until tomorrow
+pub fn next_at(parameters: &Parameters, now: DateTime<Utc>) -> Result<Prayer> {
+    let candidates = candidates(parameters, now);
+    first_defined(
+        candidates,
+        |prayer| now <= prayer.date_time(),
+        parameters,
+        now,
+    )
+}
 
-    for enum_prayer in Event::list() {
-        let prayer = Prayer::new(enum_prayer, current_date, config);
-        // Compare the prayer time with the current time
-        if current_time <= prayer.date_time().time() {
-            return prayer;
+// The prayers of the day before `now`, of its day and of the day after, in order. Midnight of the
+// day before can happen after the start of the day
+fn candidates(
+    parameters: &Parameters,
+    now: DateTime<Utc>,
+) -> impl Iterator<Item = Result<Prayer>> + '_ {
+    let current_date = now.with_timezone(&parameters.timezone).date_naive();
+    let dates = [
+        current_date.checked_sub_days(Days::new(1)),
+        Some(current_date),
+        current_date.checked_add_days(Days::new(1)),
+    ];
+    dates
+        .into_iter()
+        .flatten()
+        .flat_map(move |date| list_prayers_for_date(parameters, date))
+}
+
+// First defined prayer of the candidates that matches, an undefined event is only an error when
+// no prayer matches
+fn first_defined(
+    candidates: impl IntoIterator<Item = Result<Prayer>>,
+    matches: impl Fn(&Prayer) -> bool,
+    parameters: &Parameters,
+    now: DateTime<Utc>,
+) -> Result<Prayer> {
+    let mut undefined = None;
+    for candidate in candidates {
+        match candidate {
+            Ok(prayer) if matches(&prayer) => return Ok(prayer),
+            Ok(_) => {}
+            Err(error @ Error::UndefinedCalculation { .. }) => {
+                undefined.get_or_insert(error);
+            }
+            Err(error) => return Err(error),
         }
     }
-
-    // If no prayer in the current day found, return Time of fajr of tomorrow
-    Prayer::new(Event::Isha, current_date, config).next()
+    Err(undefined.unwrap_or(Error::UndefinedCalculation {
+        event: Event::Fajr,
+        date: now.with_timezone(&parameters.timezone).date_naive(),
+    }))
 }
 
-pub fn list_prayers_for_date(config: &Config, date: NaiveDate) -> [Prayer; 8] {
-    [
-        Prayer::new(Event::Fajr, date, config),
-        Prayer::new(Event::Sunrise, date, config),
-        Prayer::new(Event::Dhuhr, date, config),
-        Prayer::new(Event::Asr, date, config),
-        Prayer::new(Event::Sunset, date, config),
-        Prayer::new(Event::Maghrib, date, config),
-        Prayer::new(Event::Isha, date, config),
-        Prayer::new(Event::Midnight, date, config),
-    ]
+/// Prayers of a date, Dhuhr is named Jumu'ah on Fridays unless disabled in the parameters. An
+/// event that doesn't happen on this date at this location (polar day or night) is an
+/// `UndefinedCalculation` error in its place, the other events are still computed
+pub fn list_prayers_for_date(parameters: &Parameters, date: NaiveDate) -> [Result<Prayer>; 8] {
+    Event::list().map(|event| Prayer::new(event, date, parameters))
 }
 
-pub fn list_prayers(config: &Config) -> [Prayer; 8] {
-    list_prayers_for_date(config, Local::now().date_naive())
+pub fn list_prayers(parameters: &Parameters, clock: &dyn Clock) -> [Result<Prayer>; 8] {
+    list_prayers_for_date(parameters, current_date(parameters, clock))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::Config;
+    use crate::clock::FixedClock;
+    use crate::error::Error;
+    use crate::high_latitude::HighLatitudeRule;
+    use crate::iqama::IqamaRule;
+    use crate::location::Location;
     use crate::madhab::Madhab;
     use crate::method::MethodVariant;
+    use chrono::TimeZone;
+    use chrono_tz::{Africa, Asia, Europe};
+
+    fn paris_parameters() -> Parameters {
+        Parameters {
+            method: MethodVariant::FRANCE,
+            madhab: Madhab::Shafi,
+            ..Parameters::new(
+                Location {
+                    lat: 48.8566,
+                    lon: 2.3522,
+                },
+                Europe::Paris,
+            )
+        }
+    }
 
-    fn paris_config() -> Config {
-        use crate::arguments::Arguments;
-
-        let args = Arguments {
-            command: None,
-            latitude: Some(48.8566),
-            longitude: Some(2.3522),
-            timezone: Some("Europe/Paris".to_string()),
-            method: Some(MethodVariant::FRANCE),
-            madhab: Some(Madhab::Shafi),
-            fajr_mod: None,
-            dhuhr_mod: None,
-            asr_mod: None,
-            maghrib_mod: None,
-            isha_mod: None,
-            notify_before: None,
-            icon: None,
-            urgency: None,
-        };
-
-        Config::new(&args)
+    fn makkah_parameters() -> Parameters {
+        Parameters {
+            method: MethodVariant::MAKKAH,
+            madhab: Madhab::Shafi,
+            ..Parameters::new(
+                Location {
+                    lat: 21.42664,
+                    lon: 39.82563,
+                },
+                Asia::Riyadh,
+            )
+        }
     }
 
-    fn makkah_config() -> Config {
-        use crate::arguments::Arguments;
-
-        let args = Arguments {
-            command: None,
-            latitude: Some(21.42664),
-            longitude: Some(39.82563),
-            timezone: Some("Asia/Riyadh".to_string()),
-            method: Some(MethodVariant::MAKKAH),
-            madhab: Some(Madhab::Shafi),
-            fajr_mod: None,
-            dhuhr_mod: None,
-            asr_mod: None,
-            maghrib_mod: None,
-            isha_mod: None,
-            notify_before: None,
-            icon: None,
-            urgency: None,
-        };
-
-        Config::new(&args)
+    fn cairo_parameters() -> Parameters {
+        Parameters {
+            method: MethodVariant::EGYPT,
+            madhab: Madhab::Shafi,
+            ..Parameters::new(
+                Location {
+                    lat: 30.0444,
+                    lon: 31.2357,
+                },
+                Africa::Cairo,
+            )
+        }
     }
 
-    fn cairo_config() -> Config {
-        use crate::arguments::Arguments;
-
-        let args = Arguments {
-            command: None,
-            latitude: Some(30.0444),
-            longitude: Some(31.2357),
-            timezone: Some("Africa/Cairo".to_string()),
-            method: Some(MethodVariant::EGYPT),
-            madhab: Some(Madhab::Shafi),
-            fajr_mod: None,
-            dhuhr_mod: None,
-            asr_mod: None,
-            maghrib_mod: None,
-            isha_mod: None,
-            notify_before: None,
-            icon: None,
-            urgency: None,
-        };
-
-        Config::new(&args)
+    fn istanbul_parameters() -> Parameters {
+        Parameters {
+            method: MethodVariant::TURKEY,
+            madhab: Madhab::Hanafi,
+            ..Parameters::new(
+                Location {
+                    lat: 41.0082,
+                    lon: 28.9784,
+                },
+                Europe::Istanbul,
+            )
+        }
     }
 
-    fn istanbul_config() -> Config {
-        use crate::arguments::Arguments;
-
-        let args = Arguments {
-            command: None,
-            latitude: Some(41.0082),
-            longitude: Some(28.9784),
-            timezone: Some("Europe/Istanbul".to_string()),
-            method: Some(MethodVariant::TURKEY),
-            madhab: Some(Madhab::Hanafi),
-            fajr_mod: None,
-            dhuhr_mod: None,
-            asr_mod: None,
-            maghrib_mod: None,
-            isha_mod: None,
-            notify_before: None,
-            icon: None,
-            urgency: None,
-        };
-
-        Config::new(&args)
+    fn medina_parameters() -> Parameters {
+        Parameters {
+            method: MethodVariant::MAKKAH,
+            madhab: Madhab::Hanafi,
+            ..Parameters::new(
+                Location {
+                    lat: 24.5247,
+                    lon: 39.5692,
+                },
+                Asia::Riyadh,
+            )
+        }
     }
 
-    fn medina_config() -> Config {
-        use crate::arguments::Arguments;
-
-        let args = Arguments {
-            command: None,
-            latitude: Some(24.5247),
-            longitude: Some(39.5692),
-            timezone: Some("Asia/Riyadh".to_string()),
-            method: Some(MethodVariant::MAKKAH),
-            madhab: Some(Madhab::Hanafi),
-            fajr_mod: None,
-            dhuhr_mod: None,
-            asr_mod: None,
-            maghrib_mod: None,
-            isha_mod: None,
-            notify_before: None,
-            icon: None,
-            urgency: None,
-        };
-
-        Config::new(&args)
+    fn paris_parameters_with_modifications() -> Parameters {
+        Parameters {
+            method: MethodVariant::FRANCE,
+            madhab: Madhab::Shafi,
+            fajr_mod: 5,
+            dhuhr_mod: -2,
+            asr_mod: 3,
+            maghrib_mod: -1,
+            isha_mod: 4,
+            ..Parameters::new(
+                Location {
+                    lat: 48.8566,
+                    lon: 2.3522,
+                },
+                Europe::Paris,
+            )
+        }
+    }
+
+    fn kolkata_parameters() -> Parameters {
+        Parameters {
+            method: MethodVariant::KARACHI,
+            madhab: Madhab::Shafi,
+            ..Parameters::new(
+                Location {
+                    lat: 22.5726,
+                    lon: 88.3639,
+                },
+                Asia::Kolkata,
+            )
+        }
+    }
+
+    fn tehran_parameters() -> Parameters {
+        Parameters {
+            method: MethodVariant::TEHRAN,
+            madhab: Madhab::Shafi,
+            ..Parameters::new(
+                Location {
+                    lat: 35.6892,
+                    lon: 51.3890,
+                },
+                Asia::Tehran,
+            )
+        }
+    }
+
+    fn kathmandu_parameters() -> Parameters {
+        Parameters {
+            method: MethodVariant::KARACHI,
+            madhab: Madhab::Shafi,
+            ..Parameters::new(
+                Location {
+                    lat: 27.7172,
+                    lon: 85.3240,
+                },
+                Asia::Kathmandu,
+            )
+        }
     }
 
-    fn paris_config_with_modifications() -> Config {
-        use crate::arguments::Arguments;
-
-        let args = Arguments {
-            command: None,
-            latitude: Some(48.8566),
-            longitude: Some(2.3522),
-            timezone: Some("Europe/Paris".to_string()),
-            method: Some(MethodVariant::FRANCE),
-            madhab: Some(Madhab::Shafi),
-            fajr_mod: Some(5),
-            dhuhr_mod: Some(-2),
-            asr_mod: Some(3),
-            maghrib_mod: Some(-1),
-            isha_mod: Some(4),
-            notify_before: None,
-            icon: None,
-            urgency: None,
-        };
-
-        Config::new(&args)
+    fn stockholm_parameters(high_latitude: HighLatitudeRule) -> Parameters {
+        Parameters {
+            method: MethodVariant::MWL,
+            madhab: Madhab::Shafi,
+            high_latitude,
+            ..Parameters::new(
+                Location {
+                    lat: 59.33,
+                    lon: 18.06,
+                },
+                Europe::Stockholm,
+            )
+        }
     }
 
     #[test]
     fn test_paris_prayer_times_october_2_2025() {
-        let config = paris_config();
+        let parameters = paris_parameters();
         let date = NaiveDate::from_ymd_opt(2025, 10, 2).unwrap();
 
-        let prayers = list_prayers_for_date(&config, date);
+        let prayers = list_prayers_for_date(&parameters, date).map(Result::unwrap);
 
         // curl -X GET "https://api.aladhan.com/v1/timings/02-10-2025?latitude=48.8566&longitude=2.3522&method=12&timezonestring=Europe%2FParis" -H 'accept: application/json' | jq
         assert_eq!(prayers[0].time().format("%H:%M").to_string(), "06:44");
@@ -205,10 +274,10 @@ mod tests {
 
     #[test]
     fn test_makkah_prayer_times_january_1_2022() {
-        let config = makkah_config();
+        let parameters = makkah_parameters();
         let date = NaiveDate::from_ymd_opt(2022, 1, 1).unwrap();
 
-        let prayers = list_prayers_for_date(&config, date);
+        let prayers = list_prayers_for_date(&parameters, date).map(Result::unwrap);
 
         // curl -X GET "https://api.aladhan.com/v1/timings/01-01-2022?latitude=21.42664&longitude=39.82563&method=4&timezonestring=Asia%2FRiyadh" -H 'accept: application/json' | jq
         assert_eq!(prayers[0].time().format("%H:%M").to_string(), "05:37");
@@ -223,29 +292,30 @@ mod tests {
 
     #[test]
     fn test_cairo_prayer_times_march_15_2024() {
-        let config = cairo_config();
+        let parameters = cairo_parameters();
         let date = NaiveDate::from_ymd_opt(2024, 3, 15).unwrap();
 
-        let prayers = list_prayers_for_date(&config, date);
+        let prayers = list_prayers_for_date(&parameters, date).map(Result::unwrap);
 
         // curl -X GET "https://api.aladhan.com/v1/timings/15-03-2024?latitude=30.0444&longitude=31.2357&method=5&timezonestring=Africa/Cairo" -H 'accept: application/json' | jq
-        // There is one hour difference between the API response and the expected times, due to the time zone difference between Cairo and the API's Cairo time zone.
-        assert_eq!(prayers[0].time().format("%H:%M").to_string(), "05:37");
-        assert_eq!(prayers[1].time().format("%H:%M").to_string(), "07:04");
-        assert_eq!(prayers[2].time().format("%H:%M").to_string(), "13:03");
-        assert_eq!(prayers[3].time().format("%H:%M").to_string(), "16:29");
-        assert_eq!(prayers[4].time().format("%H:%M").to_string(), "19:03");
-        assert_eq!(prayers[5].time().format("%H:%M").to_string(), "19:03");
-        assert_eq!(prayers[6].time().format("%H:%M").to_string(), "20:20");
-        assert_eq!(prayers[7].time().format("%H:%M").to_string(), "01:03");
+        // The offset is the one of the date, UTC+2 since Cairo only moves to summer time at the
+        // end of April. The times used to be one hour later with the offset of the current day
+        assert_eq!(prayers[0].time().format("%H:%M").to_string(), "04:37");
+        assert_eq!(prayers[1].time().format("%H:%M").to_string(), "06:04");
+        assert_eq!(prayers[2].time().format("%H:%M").to_string(), "12:03");
+        assert_eq!(prayers[3].time().format("%H:%M").to_string(), "15:29");
+        assert_eq!(prayers[4].time().format("%H:%M").to_string(), "18:03");
+        assert_eq!(prayers[5].time().format("%H:%M").to_string(), "18:03");
+        assert_eq!(prayers[6].time().format("%H:%M").to_string(), "19:20");
+        assert_eq!(prayers[7].time().format("%H:%M").to_string(), "00:03");
     }
 
     #[test]
     fn test_istanbul_prayer_times_june_20_2024() {
-        let config = istanbul_config();
+        let parameters = istanbul_parameters();
         let date = NaiveDate::from_ymd_opt(2024, 6, 20).unwrap();
 
-        let prayers = list_prayers_for_date(&config, date);
+        let prayers = list_prayers_for_date(&parameters, date).map(Result::unwrap);
 
         // curl -X GET "https://api.aladhan.com/v1/timings/20-06-2024?latitude=41.0082&longitude=28.9784&method=13&timezonestring=Europe/Istanbul&school=1" -H 'accept: application/json' | jq
         assert_eq!(prayers[0].time().format("%H:%M").to_string(), "03:24");
@@ -260,10 +330,10 @@ mod tests {
 
     #[test]
     fn test_medina_prayer_times_september_10_2023() {
-        let config = medina_config();
+        let parameters = medina_parameters();
         let date = NaiveDate::from_ymd_opt(2023, 9, 10).unwrap();
 
-        let prayers = list_prayers_for_date(&config, date);
+        let prayers = list_prayers_for_date(&parameters, date).map(Result::unwrap);
 
         // curl -X GET "https://api.aladhan.com/v1/timings/10-09-2023?latitude=24.5247&longitude=39.5692&method=4&timezonestring=Asia/Riyadh&school=1" -H 'accept: application/json' | jq
         assert_eq!(prayers[0].time().format("%H:%M").to_string(), "04:47");
@@ -278,10 +348,10 @@ mod tests {
 
     #[test]
     fn test_paris_prayer_times_with_modifications_august_10_2024() {
-        let config = paris_config_with_modifications();
+        let parameters = paris_parameters_with_modifications();
         let date = NaiveDate::from_ymd_opt(2024, 8, 10).unwrap();
 
-        let prayers = list_prayers_for_date(&config, date);
+        let prayers = list_prayers_for_date(&parameters, date).map(Result::unwrap);
 
         // Base times from API (without modifications):
         // curl -X GET "https://api.aladhan.com/v1/timings/10-08-2024?latitude=48.8566&longitude=2.3522&method=12&timezonestring=Europe/Paris" -H 'accept: application/json' | jq
@@ -294,4 +364,214 @@ mod tests {
         assert_eq!(prayers[6].time().format("%H:%M").to_string(), "22:39"); // Isha: 22:35 + 4 min
         assert_eq!(prayers[7].time().format("%H:%M").to_string(), "01:55"); // Midnight: no modification
     }
+
+    #[test]
+    fn test_stockholm_high_latitude_rules_june_21_2026() {
+        let date = NaiveDate::from_ymd_opt(2026, 6, 21).unwrap();
+        // The sun never goes 18° below the horizon, Fajr and Isha only exist thanks to the rules
+        let cases = [
+            (HighLatitudeRule::MiddleOfTheNight, "00:49", "00:49"),
+            (HighLatitudeRule::OneSeventh, "02:44", "22:54"),
+            (HighLatitudeRule::AngleBased, "01:54", "23:39"),
+            (HighLatitudeRule::NearestLatitude, "01:29", "23:42"),
+        ];
+
+        for (rule, fajr, isha) in cases {
+            let prayers =
+                list_prayers_for_date(&stockholm_parameters(rule), date).map(Result::unwrap);
+            assert_eq!(
+                prayers[0].time().format("%H:%M").to_string(),
+                fajr,
+                "{rule}"
+            );
+            assert_eq!(prayers[1].time().format("%H:%M").to_string(), "03:31");
+            assert_eq!(prayers[5].time().format("%H:%M").to_string(), "22:08");
+            assert_eq!(
+                prayers[6].time().format("%H:%M").to_string(),
+                isha,
+                "{rule}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_paris_prayer_times_across_dst_change_march_2025() {
+        let parameters = paris_parameters();
+        let winter =
+            list_prayers_for_date(&parameters, NaiveDate::from_ymd_opt(2025, 3, 29).unwrap())
+                .map(Result::unwrap);
+        let summer =
+            list_prayers_for_date(&parameters, NaiveDate::from_ymd_opt(2025, 3, 30).unwrap())
+                .map(Result::unwrap);
+
+        // Clocks go forward on the night of March 30
+        assert_eq!(winter[2].time().format("%H:%M").to_string(), "12:55");
+        assert_eq!(winter[2].date_time().offset().to_string(), "CET");
+        assert_eq!(
+            winter[7].date_time().to_rfc3339(),
+            "2025-03-30T00:55:16+01:00"
+        );
+        assert_eq!(summer[0].time().format("%H:%M").to_string(), "06:21");
+        assert_eq!(summer[2].time().format("%H:%M").to_string(), "13:54");
+        assert_eq!(summer[2].date_time().offset().to_string(), "CEST");
+    }
+
+    #[test]
+    fn test_kolkata_prayer_times_january_15_2025() {
+        let parameters = kolkata_parameters();
+        let date = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+
+        let prayers = list_prayers_for_date(&parameters, date).map(Result::unwrap);
+
+        // UTC+05:30, Dhuhr = 12:00 + 05:30 - 88.3639° / 15 - equation of time (-9 min)
+        assert_eq!(prayers[0].time().format("%H:%M").to_string(), "05:00");
+        assert_eq!(prayers[1].time().format("%H:%M").to_string(), "06:18");
+        assert_eq!(prayers[2].time().format("%H:%M").to_string(), "11:46");
+        assert_eq!(prayers[3].time().format("%H:%M").to_string(), "14:51");
+        assert_eq!(prayers[4].time().format("%H:%M").to_string(), "17:13");
+        assert_eq!(prayers[5].time().format("%H:%M").to_string(), "17:13");
+        assert_eq!(prayers[6].time().format("%H:%M").to_string(), "18:31");
+        assert_eq!(prayers[7].time().format("%H:%M").to_string(), "23:46");
+    }
+
+    #[test]
+    fn test_tehran_prayer_times_january_15_2025() {
+        let parameters = tehran_parameters();
+        let date = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+
+        let prayers = list_prayers_for_date(&parameters, date).map(Result::unwrap);
+
+        // UTC+03:30, Dhuhr = 12:00 + 03:30 - 51.3890° / 15 - equation of time (-9 min)
+        assert_eq!(prayers[0].time().format("%H:%M").to_string(), "05:45");
+        assert_eq!(prayers[1].time().format("%H:%M").to_string(), "07:13");
+        assert_eq!(prayers[2].time().format("%H:%M").to_string(), "12:13");
+        assert_eq!(prayers[3].time().format("%H:%M").to_string(), "14:55");
+        assert_eq!(prayers[4].time().format("%H:%M").to_string(), "17:14");
+        assert_eq!(prayers[5].time().format("%H:%M").to_string(), "17:14");
+        assert_eq!(prayers[6].time().format("%H:%M").to_string(), "18:23");
+        assert_eq!(prayers[7].time().format("%H:%M").to_string(), "00:13");
+    }
+
+    #[test]
+    fn test_kathmandu_prayer_times_january_15_2025() {
+        let parameters = kathmandu_parameters();
+        let date = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+
+        let prayers = list_prayers_for_date(&parameters, date).map(Result::unwrap);
+
+        // UTC+05:45, Dhuhr = 12:00 + 05:45 - 85.3240° / 15 - equation of time (-9 min)
+        assert_eq!(prayers[0].time().format("%H:%M").to_string(), "05:33");
+        assert_eq!(prayers[1].time().format("%H:%M").to_string(), "06:55");
+        assert_eq!(prayers[2].time().format("%H:%M").to_string(), "12:13");
+        assert_eq!(prayers[3].time().format("%H:%M").to_string(), "15:10");
+        assert_eq!(prayers[4].time().format("%H:%M").to_string(), "17:30");
+        assert_eq!(prayers[5].time().format("%H:%M").to_string(), "17:30");
+        assert_eq!(prayers[6].time().format("%H:%M").to_string(), "18:52");
+        assert_eq!(prayers[7].time().format("%H:%M").to_string(), "00:13");
+    }
+
+    #[test]
+    fn test_tromso_polar_night_december_21_2025() {
+        let parameters = Parameters::new(
+            Location {
+                lat: 69.6492,
+                lon: 18.9553,
+            },
+            Europe::Oslo,
+        );
+        let date = NaiveDate::from_ymd_opt(2025, 12, 21).unwrap();
+
+        // The sun doesn't rise during the polar night, but Dhuhr is still defined
+        let prayers = list_prayers_for_date(&parameters, date);
+        assert!(matches!(
+            prayers[1],
+            Err(Error::UndefinedCalculation {
+                event: Event::Sunrise,
+                ..
+            })
+        ));
+        assert!(prayers[2].is_ok());
+
+        // Midnight of the day before is undefined too, the next prayer is still found
+        let morning = Europe::Oslo
+            .with_ymd_and_hms(2025, 12, 21, 10, 0, 0)
+            .unwrap()
+            .to_utc();
+        let next = next_at(&parameters, morning).unwrap();
+        assert!(next.date_time().to_utc() >= morning);
+        assert!(next.date_time().date_naive() == date);
+
+        // After the last event of the day, it is on the next day
+        let evening = Europe::Oslo
+            .with_ymd_and_hms(2025, 12, 21, 23, 59, 0)
+            .unwrap()
+            .to_utc();
+        let next = next_at(&parameters, evening).unwrap();
+        assert!(next.date_time().date_naive() == date.succ_opt().unwrap());
+
+        // Sunrise before Dhuhr is skipped for the current prayer
+        let current = current(&parameters, &FixedClock(morning)).unwrap();
+        assert!(current.date_time().to_utc() < morning);
+        assert_eq!(current.event(), Event::Fajr);
+    }
+
+    #[test]
+    fn test_next_prayer_at_march_equinox_2026() {
+        let parameters = paris_parameters();
+        let clock = FixedClock(
+            NaiveDate::from_ymd_opt(2026, 3, 21)
+                .unwrap()
+                .and_hms_opt(0, 30, 0)
+                .unwrap()
+                .and_utc(),
+        );
+
+        // The equation of time wraps around on that day
+        let next = next(&parameters, &clock).unwrap();
+        assert_eq!(next.event(), Event::Fajr);
+        assert_eq!(next.date_time().date_naive(), clock.0.date_naive());
+        let current = current(&parameters, &clock).unwrap();
+        assert_eq!(current.event(), Event::Isha);
+        assert!(current.time_has_passed(&clock));
+        assert_eq!(next.text_duration(&clock), "Fajr in 04H12");
+    }
+
+    #[test]
+    fn test_jumuah_on_friday() {
+        let mut parameters = paris_parameters();
+        parameters.jumuah.khutbah = Some(IqamaRule::After { minutes: 15 });
+        parameters.jumuah.iqama = Some(IqamaRule::After { minutes: 30 });
+        let friday = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+
+        let prayers = list_prayers_for_date(&parameters, friday).map(Result::unwrap);
+        let dhuhr = &prayers[2];
+        assert!(dhuhr.is_jumuah());
+        assert!(dhuhr.text_time().starts_with("Jumu'ah at "));
+        assert_eq!(
+            dhuhr.khutbah(),
+            Some(dhuhr.date_time() + chrono::Duration::minutes(15))
+        );
+        assert_eq!(
+            dhuhr.iqama(),
+            Some(dhuhr.date_time() + chrono::Duration::minutes(30))
+        );
+        assert_eq!(prayers[3].khutbah(), None);
+
+        let thursday = friday.pred_opt().unwrap();
+        assert_eq!(
+            list_prayers_for_date(&parameters, thursday)[2]
+                .as_ref()
+                .unwrap()
+                .name(),
+            "Dhuhr"
+        );
+        parameters.jumuah.enabled = false;
+        assert_eq!(
+            list_prayers_for_date(&parameters, friday)[2]
+                .as_ref()
+                .unwrap()
+                .name(),
+            "Dhuhr"
+        );
+    }
 }
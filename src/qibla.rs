@@ -0,0 +1,132 @@
+use crate::calculations::{julian_day, math, sun_position};
+use crate::location::Location;
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// Location of the Kaaba in Makkah
+pub const KAABA: Location = Location {
+    lat: 21.4225,
+    lon: 39.8262,
+};
+
+// Mean radius of the earth in kilometers
+const EARTH_RADIUS: f64 = 6371.;
+
+const COMPASS_POINTS: [&str; 16] = [
+    "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW", "NW",
+    "NNW",
+];
+
+/// Initial bearing of the great circle to the Kaaba, in degrees clockwise from true north
+pub fn bearing(location: &Location) -> f64 {
+    let delta_lon = KAABA.lon - location.lon;
+    let y = math::dsin(delta_lon) * math::dcos(KAABA.lat);
+    let x = math::dcos(location.lat) * math::dsin(KAABA.lat)
+        - math::dsin(location.lat) * math::dcos(KAABA.lat) * math::dcos(delta_lon);
+    math::darctan2(y, x).rem_euclid(360.)
+}
+
+/// Bearing from the magnetic north, `declination` is positive when the magnetic north is east of
+/// the true north
+pub fn magnetic_bearing(location: &Location, declination: f64) -> f64 {
+    (bearing(location) - declination).rem_euclid(360.)
+}
+
+/// Great circle distance to the Kaaba in kilometers
+pub fn distance(location: &Location) -> f64 {
+    // https://en.wikipedia.org/wiki/Haversine_formula
+    let half_delta_lat = (KAABA.lat - location.lat) / 2.;
+    let half_delta_lon = (KAABA.lon - location.lon) / 2.;
+    let a = math::dsin(half_delta_lat).powi(2)
+        + math::dcos(location.lat) * math::dcos(KAABA.lat) * math::dsin(half_delta_lon).powi(2);
+    2. * EARTH_RADIUS * a.sqrt().asin()
+}
+
+/// Compass point (N, NNE, NE, ...) of a bearing in degrees
+pub fn compass_point(bearing: f64) -> &'static str {
+    let index = (bearing.rem_euclid(360.) / 22.5).round() as usize % COMPASS_POINTS.len();
+    COMPASS_POINTS[index]
+}
+
+// Solar noon in Makkah, as a julian date
+fn makkah_noon(date: NaiveDate) -> f64 {
+    // Julian dates start at noon UTC
+    let julian_date = julian_day(date);
+    let (_, equation_of_time) = sun_position(julian_date);
+    julian_date - (KAABA.lon / 15. + equation_of_time) / 24.
+}
+
+/// Moments of the year when the sun is directly over the Kaaba, the shadows then point away from
+/// the Qibla wherever the sun is visible
+pub fn qibla_days(year: i32, timezone: Tz) -> Vec<DateTime<Tz>> {
+    let (Some(first_day), Some(last_day)) = (
+        NaiveDate::from_ymd_opt(year, 1, 1),
+        NaiveDate::from_ymd_opt(year, 12, 31),
+    ) else {
+        return Vec::new();
+    };
+
+    // Distance between the declination of the sun and the latitude of the Kaaba at Makkah noon
+    let gap = |date: NaiveDate| sun_position(makkah_noon(date)).0 - KAABA.lat;
+
+    first_day
+        .iter_days()
+        .take_while(|date| *date < last_day)
+        .filter_map(|date| {
+            let next_date = date.succ_opt()?;
+            let (gap_today, gap_tomorrow) = (gap(date), gap(next_date));
+            if gap_today.signum() == gap_tomorrow.signum() {
+                return None;
+            }
+            // The sun passes over the Kaaba at noon of the closest day
+            let day = if gap_today.abs() <= gap_tomorrow.abs() {
+                date
+            } else {
+                next_date
+            };
+            Some(julian_date_time(makkah_noon(day)).with_timezone(&timezone))
+        })
+        .collect()
+}
+
+fn julian_date_time(julian_date: f64) -> DateTime<Utc> {
+    // 2000-01-01 12:00 UTC
+    let j2000 = Utc.with_ymd_and_hms(2000, 1, 1, 12, 0, 0).unwrap();
+    j2000 + Duration::seconds(((julian_date - 2451545.) * 86400.).round() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, Timelike};
+
+    #[test]
+    fn test_bearing_and_distance() {
+        let paris = Location {
+            lat: 48.8566,
+            lon: 2.3522,
+        };
+        assert!((bearing(&paris) - 119.2).abs() < 0.1);
+        assert_eq!(compass_point(bearing(&paris)), "ESE");
+        assert!((distance(&paris) - 4500.).abs() < 10.);
+        assert!((magnetic_bearing(&paris, 2.) - 117.2).abs() < 0.1);
+
+        let new_york = Location {
+            lat: 40.7128,
+            lon: -74.006,
+        };
+        assert!((bearing(&new_york) - 58.5).abs() < 0.1);
+        assert_eq!(compass_point(bearing(&new_york)), "ENE");
+        assert!((distance(&new_york) - 10300.).abs() < 20.);
+    }
+
+    #[test]
+    fn test_qibla_days_2026() {
+        let days = qibla_days(2026, chrono_tz::UTC);
+        assert_eq!(days.len(), 2);
+        assert_eq!((days[0].month(), days[0].day()), (5, 28));
+        assert_eq!((days[0].hour(), days[0].minute() / 10), (9, 1));
+        assert_eq!((days[1].month(), days[1].day()), (7, 15));
+        assert_eq!((days[1].hour(), days[1].minute() / 10), (9, 2));
+    }
+}
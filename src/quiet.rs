@@ -0,0 +1,181 @@
+use crate::app_error::Error;
+use crate::notification::NotificationKind;
+use chrono::{DateTime, Datelike, Days, NaiveDateTime, NaiveTime, SecondsFormat, Utc, Weekday};
+use prayer_times::Event;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+// Notifications the daemon doesn't send
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct QuietConfig {
+    // Events never notified
+    pub muted: Vec<Event>,
+    pub windows: Vec<QuietWindow>,
+}
+
+// Time of the day without notifications, it ends the next day when `to` is before `from`
+#[derive(Serialize, Deserialize, Clone)]
+pub struct QuietWindow {
+    pub from: NaiveTime,
+    pub to: NaiveTime,
+    // Days the window starts, every day when empty
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+    // Kinds of notification suppressed, all of them when empty
+    #[serde(default)]
+    pub kinds: Vec<NotificationKind>,
+}
+
+impl QuietWindow {
+    fn contains(&self, kind: NotificationKind, moment: NaiveDateTime) -> bool {
+        if !self.kinds.is_empty() && !self.kinds.contains(&kind) {
+            return false;
+        }
+        let starts_on = |weekday: Weekday| self.days.is_empty() || self.days.contains(&weekday);
+        let time = moment.time();
+        let date = moment.date();
+        if self.from <= self.to {
+            starts_on(date.weekday()) && self.from <= time && time < self.to
+        } else {
+            // After its start, or the morning after a day it started
+            let yesterday = date.checked_sub_days(Days::new(1)).unwrap_or(date);
+            (starts_on(date.weekday()) && self.from <= time)
+                || (starts_on(yesterday.weekday()) && time < self.to)
+        }
+    }
+}
+
+impl QuietConfig {
+    // Why a notification is suppressed, `moment` is the local time and `paused_until` the end of
+    // the pause
+    pub fn suppression(
+        &self,
+        kind: NotificationKind,
+        event: Event,
+        moment: NaiveDateTime,
+        paused_until: Option<DateTime<Utc>>,
+    ) -> Option<String> {
+        if self.muted.contains(&event) {
+            return Some(format!("{} is muted", event));
+        }
+        if let Some(window) = self
+            .windows
+            .iter()
+            .find(|window| window.contains(kind, moment))
+        {
+            return Some(format!(
+                "quiet hours from {} to {}",
+                window.from.format("%H:%M"),
+                window.to.format("%H:%M")
+            ));
+        }
+        paused_until.map(|until| format!("paused until {}", until.to_rfc3339()))
+    }
+}
+
+// File holding the end of the pause set with `pause`, in the state directory
+pub fn pause_file() -> PathBuf {
+    let state = match std::env::var_os("XDG_STATE_HOME") {
+        Some(state) if !state.is_empty() => PathBuf::from(state),
+        _ => PathBuf::from(std::env::var_os("HOME").unwrap_or_default()).join(".local/state"),
+    };
+    state.join(env!("CARGO_PKG_NAME")).join("pause")
+}
+
+pub fn pause(file: &Path, until: DateTime<Utc>) -> Result<(), Error> {
+    let write = || {
+        if let Some(directory) = file.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+        std::fs::write(file, until.to_rfc3339_opts(SecondsFormat::Secs, true))
+    };
+    write().map_err(|error| Error::Io(format!("Failed to write {}: {}", file.display(), error)))
+}
+
+pub fn resume(file: &Path) -> Result<(), Error> {
+    match std::fs::remove_file(file) {
+        Err(error) if error.kind() != std::io::ErrorKind::NotFound => Err(Error::Io(format!(
+            "Failed to remove {}: {}",
+            file.display(),
+            error
+        ))),
+        _ => Ok(()),
+    }
+}
+
+// End of the pause when it is still running at `now`
+pub fn paused_until(file: &Path, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let content = std::fs::read_to_string(file).ok()?;
+    DateTime::parse_from_rfc3339(content.trim())
+        .ok()
+        .map(|until| until.to_utc())
+        .filter(|until| now < *until)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_quiet_windows() {
+        let quiet = QuietConfig {
+            muted: vec![Event::Midnight],
+            windows: vec![
+                // Meetings on Monday morning, only the reminders
+                QuietWindow {
+                    from: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+                    to: NaiveTime::from_hms_opt(12, 0, 0).unwrap(),
+                    days: vec![Weekday::Mon],
+                    kinds: vec![NotificationKind::Reminder],
+                },
+                // Friday night to Saturday morning
+                QuietWindow {
+                    from: NaiveTime::from_hms_opt(23, 0, 0).unwrap(),
+                    to: NaiveTime::from_hms_opt(5, 0, 0).unwrap(),
+                    days: vec![Weekday::Fri],
+                    kinds: Vec::new(),
+                },
+            ],
+        };
+        // 2026-03-16 is a Monday, 2026-03-21 a Saturday
+        let at = |day, hour| {
+            NaiveDate::from_ymd_opt(2026, 3, day)
+                .unwrap()
+                .and_hms_opt(hour, 0, 0)
+                .unwrap()
+        };
+        let suppressed = |kind, event, moment| quiet.suppression(kind, event, moment, None);
+
+        assert!(suppressed(NotificationKind::Adhan, Event::Midnight, at(18, 0)).is_some());
+        assert!(suppressed(NotificationKind::Reminder, Event::Dhuhr, at(16, 10)).is_some());
+        assert!(suppressed(NotificationKind::Adhan, Event::Dhuhr, at(16, 10)).is_none());
+        assert!(suppressed(NotificationKind::Reminder, Event::Dhuhr, at(17, 10)).is_none());
+        assert!(suppressed(NotificationKind::Adhan, Event::Fajr, at(21, 4)).is_some());
+        assert!(suppressed(NotificationKind::Adhan, Event::Fajr, at(22, 4)).is_none());
+
+        let until = at(18, 14).and_utc();
+        let paused =
+            quiet.suppression(NotificationKind::Adhan, Event::Asr, at(18, 12), Some(until));
+        assert_eq!(
+            paused,
+            Some("paused until 2026-03-18T14:00:00+00:00".to_string())
+        );
+    }
+
+    #[test]
+    fn test_pause_file() {
+        let file = std::env::temp_dir()
+            .join(format!("prayer-times-pause-{}", std::process::id()))
+            .join("pause");
+        let now = Utc::now();
+        assert_eq!(paused_until(&file, now), None);
+        pause(&file, now + chrono::Duration::hours(2)).unwrap();
+        assert!(paused_until(&file, now).is_some());
+        assert_eq!(paused_until(&file, now + chrono::Duration::hours(3)), None);
+        resume(&file).unwrap();
+        assert_eq!(paused_until(&file, now), None);
+        std::fs::remove_dir_all(file.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,125 @@
+use crate::app_error::Error;
+use crate::daemon::Message;
+use nix::sys::signal::{SigSet, Signal};
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+// Time between two checks of the config file
+const POLL_SECONDS: u64 = 2;
+
+// Ask the daemon to read its config again on SIGHUP, from the returned thread. It must be called
+// before starting other threads, they inherit the blocked signal so that only the waiting thread
+// receives it
+pub fn watch_hangup(messages: Sender<Message>) -> Result<JoinHandle<()>, Error> {
+    let signals = SigSet::from(Signal::SIGHUP);
+    signals
+        .thread_block()
+        .map_err(|error| Error::Io(format!("Failed to block SIGHUP: {}", error)))?;
+    Ok(thread::spawn(move || loop {
+        match signals.wait() {
+            Ok(_) => {
+                // The daemon is gone when the channel is closed
+                if messages.send(Message::Reload("SIGHUP")).is_err() {
+                    return;
+                }
+            }
+            Err(error) => {
+                eprintln!("Failed to wait for SIGHUP: {}", error);
+                return;
+            }
+        }
+    }))
+}
+
+// Ask the daemon to read its config again when the file is modified. A removed file is ignored,
+// reading the config would write the default one
+pub fn watch_file(file: PathBuf, messages: Sender<Message>) {
+    poll_file(file, Duration::from_secs(POLL_SECONDS), messages);
+}
+
+fn poll_file(file: PathBuf, interval: Duration, messages: Sender<Message>) {
+    let modified = move || {
+        std::fs::metadata(&file)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    };
+    thread::spawn(move || {
+        let mut last: Option<SystemTime> = modified();
+        loop {
+            thread::sleep(interval);
+            let current = modified();
+            if current.is_some() && current != last {
+                if messages
+                    .send(Message::Reload("config file changed"))
+                    .is_err()
+                {
+                    return;
+                }
+                last = current;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nix::sys::pthread::pthread_kill;
+    use std::os::unix::thread::JoinHandleExt;
+    use std::sync::mpsc::{self, Receiver};
+
+    fn reason(messages: &Receiver<Message>) -> Option<&'static str> {
+        match messages.recv_timeout(Duration::from_secs(2)) {
+            Ok(Message::Reload(reason)) => Some(reason),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_watch_hangup() {
+        let (sender, messages) = mpsc::channel();
+        // Run from its own thread, the other tests don't block the signal
+        let waiting = thread::spawn(move || watch_hangup(sender).unwrap())
+            .join()
+            .unwrap();
+        // Only the waiting thread gets the signal, the test process is not hung up
+        pthread_kill(waiting.as_pthread_t(), Signal::SIGHUP).unwrap();
+        assert_eq!(reason(&messages), Some("SIGHUP"));
+        pthread_kill(waiting.as_pthread_t(), Signal::SIGHUP).unwrap();
+        assert_eq!(reason(&messages), Some("SIGHUP"));
+    }
+
+    #[test]
+    fn test_watch_file() {
+        let directory =
+            std::env::temp_dir().join(format!("prayer-times-watch-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let file = directory.join("config.toml");
+        std::fs::write(&file, "").unwrap();
+        let set_modified = |seconds| {
+            let time = SystemTime::UNIX_EPOCH + Duration::from_secs(seconds);
+            std::fs::File::options()
+                .write(true)
+                .open(&file)
+                .and_then(|opened| opened.set_modified(time))
+                .unwrap();
+        };
+        let (sender, messages) = mpsc::channel();
+        poll_file(file.clone(), Duration::from_millis(10), sender);
+
+        // Nothing until the file changes
+        assert!(messages.recv_timeout(Duration::from_millis(100)).is_err());
+        set_modified(1_000_000);
+        assert_eq!(reason(&messages), Some("config file changed"));
+
+        // Its removal is not a change, its return is
+        std::fs::remove_file(&file).unwrap();
+        assert!(messages.recv_timeout(Duration::from_millis(100)).is_err());
+        std::fs::write(&file, "").unwrap();
+        set_modified(2_000_000);
+        assert_eq!(reason(&messages), Some("config file changed"));
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,282 @@
+use crate::app_error::Error;
+use crate::output::{self, OutputFormat, UNDEFINED_TIME};
+use chrono::{DateTime, NaiveDate};
+use chrono_tz::Tz;
+use prayer_times::locale::format_template;
+use prayer_times::{Event, Locale, Parameters, Prayer};
+use serde_json::{Map, Value};
+use std::io::IsTerminal;
+
+const DATE_FORMAT: &str = "%a %Y-%m-%d";
+const TIME_FORMAT: &str = "%H:%M";
+const COLUMN_SEPARATOR: &str = "  ";
+// Written after the row of today when there are no colors
+const TODAY_MARKER: &str = "<";
+
+struct Day {
+    date: NaiveDate,
+    prayers: Vec<Option<Prayer>>,
+}
+
+// A column of the timetable: the index of the event and whether it shows its iqama
+#[derive(Clone, Copy)]
+struct Column {
+    event: usize,
+    iqama: bool,
+}
+
+impl Column {
+    fn time(&self, day: &Day) -> Option<DateTime<Tz>> {
+        let prayer = day.prayers[self.event].as_ref()?;
+        if self.iqama {
+            prayer.iqama()
+        } else {
+            Some(prayer.date_time())
+        }
+    }
+}
+
+// Columns of the events, followed by their iqama when one of the days has it
+fn columns(days: &[Day]) -> Vec<Column> {
+    (0..Event::list().len())
+        .flat_map(|event| {
+            let has_iqama = days.iter().any(|day| {
+                day.prayers[event]
+                    .as_ref()
+                    .is_some_and(|prayer| prayer.iqama().is_some())
+            });
+            let adhan = Column {
+                event,
+                iqama: false,
+            };
+            let iqama = Column { event, iqama: true };
+            [Some(adhan), has_iqama.then_some(iqama)]
+        })
+        .flatten()
+        .collect()
+}
+
+// The days between two dates (included)
+fn days(parameters: &Parameters, from: NaiveDate, to: NaiveDate) -> Result<Vec<Day>, Error> {
+    if to < from {
+        return Err(Error::InvalidRange { from, to });
+    }
+    Ok(from
+        .iter_days()
+        .take_while(|date| *date <= to)
+        .map(|date| Day {
+            date,
+            prayers: Event::list()
+                .iter()
+                .map(|event| Prayer::new(*event, date, parameters).ok())
+                .collect(),
+        })
+        .collect())
+}
+
+// Print a row for each day between two dates (included), with a column for each event
+pub fn print_timetable(
+    parameters: &Parameters,
+    from: NaiveDate,
+    to: NaiveDate,
+    today: NaiveDate,
+    format: OutputFormat,
+) -> Result<(), Error> {
+    let days = days(parameters, from, to)?;
+    let columns = columns(&days);
+    if format == OutputFormat::Plain {
+        // Colors are only used in a terminal so the table can be written to a file
+        let highlight = std::io::stdout().is_terminal();
+        for line in table(&days, &columns, parameters.locale, today, highlight) {
+            println!("{}", line);
+        }
+        return Ok(());
+    }
+
+    let values: Vec<Value> = days
+        .iter()
+        .map(|day| {
+            let mut object = Map::new();
+            object.insert("date".to_string(), Value::from(day.date.to_string()));
+            for column in &columns {
+                let event = Event::list()[column.event];
+                let key = if column.iqama {
+                    format!("{} iqama", event)
+                } else {
+                    event.to_string()
+                };
+                let time = column
+                    .time(day)
+                    .map(|time| Value::from(time.to_rfc3339()))
+                    .unwrap_or(Value::Null);
+                object.insert(key, time);
+            }
+            Value::Object(object)
+        })
+        .collect();
+    output::print_values(&values, format, true)
+}
+
+fn table(
+    days: &[Day],
+    columns: &[Column],
+    locale: Locale,
+    today: NaiveDate,
+    highlight: bool,
+) -> Vec<String> {
+    let mut header = vec!["Date".to_string()];
+    header.extend(columns.iter().map(|column| {
+        let name = locale.event_name(Event::list()[column.event]);
+        if column.iqama {
+            format_template(locale.messages().iqama, &[("event", name)])
+        } else {
+            name.to_string()
+        }
+    }));
+
+    let rows: Vec<Vec<String>> = days
+        .iter()
+        .map(|day| {
+            let mut row = vec![day.date.format(DATE_FORMAT).to_string()];
+            row.extend(columns.iter().map(|column| match column.time(day) {
+                Some(time) => time.format(TIME_FORMAT).to_string(),
+                None => UNDEFINED_TIME.to_string(),
+            }));
+            row
+        })
+        .collect();
+
+    let widths: Vec<usize> = (0..header.len())
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .chain([header[column].chars().count()])
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+    let format_row = |row: &[String]| -> String {
+        row.iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join(COLUMN_SEPARATOR)
+            .trim_end()
+            .to_string()
+    };
+
+    let header_line = format_row(&header);
+    let mut lines = if highlight {
+        vec![format!("\x1b[1m{}\x1b[0m", header_line)]
+    } else {
+        let rule = "-".repeat(header_line.chars().count());
+        vec![header_line, rule]
+    };
+    for (day, row) in days.iter().zip(&rows) {
+        let line = format_row(row);
+        if day.date != today {
+            lines.push(line);
+        } else if highlight {
+            lines.push(format!("\x1b[7m{}\x1b[0m", line));
+        } else {
+            lines.push(format!("{}{}{}", line, COLUMN_SEPARATOR, TODAY_MARKER));
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arguments::month_range;
+    use crate::config::test_config;
+
+    fn paris() -> Parameters {
+        test_config("location = { lat = 48.8566, lon = 2.3522 }\ntimezone = 'Europe/Paris'")
+            .parameters()
+    }
+
+    #[test]
+    fn test_month_bounds() {
+        let (from, to) = month_range(NaiveDate::from_ymd_opt(2028, 2, 14).unwrap());
+        let days = days(&paris(), from, to).unwrap();
+        assert_eq!(days.len(), 29);
+        assert_eq!(days[0].date, NaiveDate::from_ymd_opt(2028, 2, 1).unwrap());
+        assert_eq!(days[28].date, NaiveDate::from_ymd_opt(2028, 2, 29).unwrap());
+    }
+
+    #[test]
+    fn test_range_bounds() {
+        let from = NaiveDate::from_ymd_opt(2026, 3, 30).unwrap();
+        let to = NaiveDate::from_ymd_opt(2026, 4, 2).unwrap();
+        let dates: Vec<NaiveDate> = days(&paris(), from, to)
+            .unwrap()
+            .iter()
+            .map(|day| day.date)
+            .collect();
+        assert_eq!(dates, from.iter_days().take(4).collect::<Vec<_>>());
+
+        let single = days(&paris(), from, from).unwrap();
+        assert_eq!(single.len(), 1);
+        assert!(matches!(
+            days(&paris(), to, from),
+            Err(Error::InvalidRange { .. })
+        ));
+    }
+
+    #[test]
+    fn test_today_marker() {
+        let from = NaiveDate::from_ymd_opt(2026, 3, 19).unwrap();
+        let today = from.succ_opt().unwrap();
+        let days = days(&paris(), from, today.succ_opt().unwrap()).unwrap();
+        let columns = columns(&days);
+
+        let lines = table(&days, &columns, Locale::En, today, false);
+        assert!(lines[0].starts_with("Date"));
+        assert!(lines[1].chars().all(|c| c == '-'));
+        assert!(!lines[2].ends_with(TODAY_MARKER));
+        assert!(lines[3].starts_with("Fri 2026-03-20"));
+        assert!(lines[3].ends_with(TODAY_MARKER));
+        assert!(!lines[4].ends_with(TODAY_MARKER));
+
+        let lines = table(&days, &columns, Locale::En, today, true);
+        assert_eq!(lines.len(), 4);
+        assert!(lines[2].starts_with("\x1b[7mFri 2026-03-20"));
+        assert!(!lines[1].contains("\x1b[7m"));
+    }
+
+    #[test]
+    fn test_localized_header() {
+        let mut parameters = test_config(
+            "location = { lat = 48.8566, lon = 2.3522 }\ntimezone = 'Europe/Paris'\n\
+             [prayer.iqama]\nFajr = { rule = 'After', minutes = 20 }",
+        )
+        .parameters();
+        parameters.locale = Locale::Fr;
+        let date = NaiveDate::from_ymd_opt(2026, 3, 19).unwrap();
+        let days = days(&parameters, date, date).unwrap();
+        let columns = columns(&days);
+
+        let header = &table(&days, &columns, parameters.locale, date, false)[0];
+        let cells: Vec<&str> = header
+            .split(COLUMN_SEPARATOR)
+            .map(str::trim)
+            .filter(|cell| !cell.is_empty())
+            .collect();
+        assert_eq!(
+            cells,
+            [
+                "Date",
+                "Fajr",
+                "Iqama Fajr",
+                "Lever du soleil",
+                "Dhuhr",
+                "Asr",
+                "Coucher du soleil",
+                "Maghrib",
+                "Isha",
+                "Minuit"
+            ]
+        );
+    }
+}